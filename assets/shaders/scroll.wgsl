@@ -2,6 +2,7 @@
 
 struct ScrollMaterial {
 	scroll_speed : f32,
+	offset : f32,
 	rect : vec4<f32>,
 }
 
@@ -18,7 +19,7 @@ fn fragment (
 	#import bevy_sprite::mesh2d_vertex_output
 ) -> @location(0) vec4<f32> {
 	var uv : vec2<f32> = uv;
-	uv.x += material.scroll_speed * globals.time;
+	uv.x += material.offset;
 	uv.x = fract(uv.x);
 
 	var texture_size_int : vec2<i32> = textureDimensions(texture);
@@ -0,0 +1,122 @@
+//! Compares testing the plane against every collider, as `resolve_collisions`
+//! used to, with going through the sweep and prune broadphase first. Every
+//! collider moves each tick, as obstacles do in the game, so the broadphase
+//! is timed either built from scratch or updated in place, then queried.
+//!
+//! Run with `cargo bench --bench broadphase`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use bevy::prelude::*;
+use tappy_plane::physics::broadphase::Broadphase;
+use tappy_plane::physics::geometry::{rect_points, sat};
+
+const TICKS : u32 = 2_000;
+
+fn main () {
+	println!("{:>10} {:>14} {:>14} {:>14}", "colliders", "brute force", "rebuild", "update");
+	
+	for columns in [4, 16, 64, 256, 1024] {
+		let colliders = obstacle_field(columns);
+		let player = rect_points(Vec2::new(-180., -20.), Vec2::new(-130., 25.));
+		
+		// Where every collider is on a tick, scrolling left a pixel a tick
+		let placed = |tick : u32| colliders.iter().enumerate().map(move |(i, (t, collider))| {
+			let t = *t - Vec2::new(tick as f32, 0.);
+			(Entity::from_raw(i as u32), collider.iter().map(|p| *p + t).collect::<Vec<_>>())
+		});
+		
+		let query = |broadphase : &Broadphase| {
+			broadphase
+				.query(Vec2::new(-180., -20.), Vec2::new(-130., 25.))
+				.filter(|proxy| sat(&player, &proxy.points).is_some())
+				.count()
+		};
+		
+		let mut tick = 0;
+		
+		let brute = time(|| {
+			tick += 1;
+			
+			placed(tick)
+				.filter(|(_, points)| sat(&player, points).is_some())
+				.count()
+		});
+		
+		let mut tick = 0;
+		
+		let rebuild = time(|| {
+			tick += 1;
+			
+			let mut broadphase = Broadphase::default();
+			
+			for (entity, points) in placed(tick) {
+				broadphase.update(entity, points);
+			}
+			
+			broadphase.build();
+			query(&broadphase)
+		});
+		
+		let mut broadphase = Broadphase::default();
+		let mut tick = 0;
+		
+		let update = time(|| {
+			tick += 1;
+			
+			for (entity, points) in placed(tick) {
+				broadphase.update(entity, points);
+			}
+			
+			broadphase.build();
+			query(&broadphase)
+		});
+		
+		println!(
+			"{:>10} {:>14?} {:>14?} {:>14?}",
+			colliders.len(), brute, rebuild, update,
+		);
+	}
+}
+
+/// Average time per tick
+fn time (mut tick : impl FnMut() -> usize) -> Duration {
+	let start = Instant::now();
+	
+	for _ in 0..TICKS {
+		black_box(tick());
+	}
+	
+	start.elapsed() / TICKS
+}
+
+/// Columns of rocks laid out like the obstacle spawner does, a rock above and
+/// below the gap plus two decorative children each
+fn obstacle_field (columns : usize) -> Vec<(Vec2, Vec<Vec2>)> {
+	let down = vec![
+		Vec2::new(-50., 119.5),
+		Vec2::new(50., 119.5),
+		Vec2::new(15., -119.5),
+		Vec2::new(10., -119.5),
+	];
+	
+	let up = vec![
+		Vec2::new(10., 119.5),
+		Vec2::new(15., 119.5),
+		Vec2::new(50., -119.5),
+		Vec2::new(-50., -119.5),
+	];
+	
+	let mut colliders = Vec::new();
+	
+	for i in 0..columns {
+		let x = -400. + i as f32 * 250.;
+		
+		for dx in [-55., 0., 55.] {
+			colliders.push((Vec2::new(x + dx, 210.), down.clone()));
+			colliders.push((Vec2::new(x + dx, -210.), up.clone()));
+		}
+	}
+	
+	colliders
+}
@@ -0,0 +1,228 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::storage::Storage;
+
+/// Storage key the bindings are saved under
+const BINDINGS_KEY : &str = "controls";
+
+/// Actions the player can rebind from the settings screen
+pub const REBINDABLE : [Action; 3] = [Action::Flap, Action::Pause, Action::Confirm];
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.init_resource::<Input<Action>>()
+			.init_resource::<ActionBindings>()
+			.add_startup_system(load_bindings)
+			.add_system(
+				update_actions
+					.in_set(ActionsSet)
+					.in_base_set(CoreSet::PreUpdate)
+					.after(InputSystem)
+			)
+		;
+	}
+}
+
+// Sets
+// =========================================================================
+
+/// Where `Input<Action>` is updated from the devices, in `PreUpdate`
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ActionsSet;
+
+// Structs
+// =========================================================================
+
+/// Something the player can do, independent of the device used to do it.
+/// Read it like any other input, with `Res<Input<Action>>`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Action {
+	Flap,
+	Confirm,
+	Pause,
+	Back,
+}
+
+/// A physical input that can trigger an action
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+	Key(KeyCode),
+	Mouse(MouseButton),
+	/// Any finger on the screen
+	Touch,
+	/// A button on any connected gamepad
+	Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+	/// Upper case name for showing on screen
+	pub fn name (&self) -> String {
+		match self {
+			Binding::Key(key) => format!("{:?}", key),
+			Binding::Mouse(button) => format!("Mouse {:?}", button),
+			Binding::Touch => "Touch".into(),
+			Binding::Gamepad(button) => format!("Pad {:?}", button),
+		}.to_uppercase()
+	}
+}
+
+// Resources
+// =========================================================================
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct ActionBindings (pub Vec<(Binding, Action)>);
+
+impl ActionBindings {
+	pub fn for_action (&self, action : Action) -> impl Iterator<Item = Binding> + '_ {
+		self.0.iter()
+			.filter(move |(_, a)| *a == action)
+			.map(|(binding, _)| *binding)
+	}
+	
+	/// Makes `binding` the only mouse, key or gamepad binding for `action`.
+	/// Touch is left alone so touch screens can always play.
+	pub fn rebind (&mut self, action : Action, binding : Binding) {
+		self.0.retain(|(b, a)| *a != action || *b == Binding::Touch);
+		self.0.push((binding, action));
+	}
+	
+	pub fn save (&self, storage : &Storage) {
+		storage.save(BINDINGS_KEY, self);
+	}
+}
+
+impl Default for ActionBindings {
+	fn default () -> Self {
+		use Action::*;
+		use Binding::*;
+		
+		ActionBindings(vec![
+			(Mouse(MouseButton::Left), Flap),
+			(Touch, Flap),
+			(Key(KeyCode::Space), Flap),
+			(Key(KeyCode::Up), Flap),
+			(Key(KeyCode::W), Flap),
+			(Gamepad(GamepadButtonType::South), Flap),
+			
+			(Mouse(MouseButton::Left), Confirm),
+			(Touch, Confirm),
+			(Key(KeyCode::Space), Confirm),
+			(Key(KeyCode::Return), Confirm),
+			(Gamepad(GamepadButtonType::South), Confirm),
+			(Gamepad(GamepadButtonType::Start), Confirm),
+			
+			(Key(KeyCode::Escape), Pause),
+			(Key(KeyCode::P), Pause),
+			(Gamepad(GamepadButtonType::Start), Pause),
+			
+			(Key(KeyCode::Escape), Back),
+			(Key(KeyCode::Back), Back),
+			(Gamepad(GamepadButtonType::East), Back),
+		])
+	}
+}
+
+// Helpers
+// =========================================================================
+
+/// Where the screen was clicked or tapped this frame to trigger `action`,
+/// in world space. Only the mouse buttons bound to it count, and taps if
+/// touch is. Assumes the camera sits unscaled at the origin.
+pub fn pointer_press (
+	window : &Window,
+	bindings : &ActionBindings,
+	action : Action,
+	mouse : &Input<MouseButton>,
+	touch : &Touches,
+) -> Option<Vec2> {
+	let size = Vec2::new(window.width(), window.height());
+	let mut clicked = false;
+	
+	for binding in bindings.for_action(action) {
+		match binding {
+			Binding::Touch => {
+				if let Some(touch) = touch.iter_just_pressed().next() {
+					// Touches are measured from the top left
+					let position = touch.position();
+					return Some(Vec2::new(position.x, size.y - position.y) - size * 0.5);
+				}
+			}
+			Binding::Mouse(button) => clicked |= mouse.just_pressed(button),
+			Binding::Key(_) | Binding::Gamepad(_) => {}
+		}
+	}
+	
+	if !clicked { return None; }
+	
+	window.cursor_position().map(|position| position - size * 0.5)
+}
+
+// Systems
+// =========================================================================
+
+fn load_bindings (
+	storage : Res<Storage>,
+	mut bindings : ResMut<ActionBindings>,
+) {
+	if let Some(saved) = storage.load(BINDINGS_KEY) {
+		*bindings = saved;
+	}
+}
+
+fn update_actions (
+	mut actions : ResMut<Input<Action>>,
+	bindings : Res<ActionBindings>,
+	keys : Res<Input<KeyCode>>,
+	mouse : Res<Input<MouseButton>>,
+	touch : Res<Touches>,
+	gamepads : Res<Gamepads>,
+	buttons : Res<Input<GamepadButton>>,
+) {
+	actions.clear();
+	
+	for action in [Action::Flap, Action::Confirm, Action::Pause, Action::Back] {
+		let mut held = false;
+		let mut tapped = false;
+		
+		for binding in bindings.for_action(action) {
+			match binding {
+				Binding::Key(key) => {
+					held |= keys.pressed(key);
+					tapped |= keys.just_pressed(key);
+				}
+				Binding::Mouse(button) => {
+					held |= mouse.pressed(button);
+					tapped |= mouse.just_pressed(button);
+				}
+				Binding::Touch => {
+					held |= touch.iter().next().is_some();
+					tapped |= touch.any_just_pressed();
+				}
+				Binding::Gamepad(button_type) => {
+					for gamepad in gamepads.iter() {
+						let button = GamepadButton::new(gamepad, button_type);
+						held |= buttons.pressed(button);
+						tapped |= buttons.just_pressed(button);
+					}
+				}
+			}
+		}
+		
+		// Pressed afresh whenever any binding is, even while another binding
+		// is already holding the action. A tap can start and end within a
+		// frame, so it counts as held for that frame too.
+		if tapped {
+			actions.reset(action);
+			actions.press(action);
+		} else if held {
+			actions.press(action);
+			actions.clear_just_pressed(action);
+		} else {
+			actions.release(action);
+		}
+	}
+}
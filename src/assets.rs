@@ -1,5 +1,93 @@
 use bevy::prelude::*;
 use bevy::utils::HashMap;
+use crate::physics::alpha_hulls;
+
+/// Height of the bands sprites are cut into when building collision hulls
+const HULL_BAND : usize = 16;
+
+/// Name and rect (x, y, width, height) of every sprite on the sheet, in atlas
+/// index order
+pub const SPRITES : &[(&str, f32, f32, f32, f32)] = &[
+	("UIbg",          0., 986., 264., 264.),
+	("background",    0., 355., 800., 480.),
+	("buttonLarge",   0., 1250., 196., 70.),
+	("buttonSmall",   0., 1320., 136., 80.),
+	("groundDirt",    0., 0., 808., 71.),
+	("groundGrass",   0., 142.3, 808., 71.),
+	("groundIce",     0., 71.3, 808., 71.),
+	("groundRock",    0., 284.3, 808., 71.),
+	("groundSnow",    0., 213.3, 808., 71.),
+	("letterA",       412., 835.3, 61., 64. - 0.5),
+	("letterB",       487., 1537.3, 50., 66. - 0.5),
+	("letterC",       460., 977.3, 52., 66. - 0.5),
+	("letterD",       432., 1613.3, 54., 66. - 0.5),
+	("letterE",       511., 1965.3, 45., 64. - 0.5),
+	("letterF",       512., 963.3, 44., 64. - 0.5),
+	("letterG",       460., 1107.3, 52., 66. - 0.5),
+	("letterH",       473., 835.3, 51., 64. - 0.5),
+	("letterI",       524., 835.3, 22., 64. - 0.5),
+	("letterJ",       512., 1027.3, 42., 66. - 0.5),
+	("letterK",       432., 1821.3, 53., 64. - 0.5),
+	("letterL",       512., 899.3, 44., 64. - 0.5),
+	("letterM",       392., 1967.3, 66., 64. - 0.5),
+	("letterN",       432., 1679.3, 53., 64. - 0.5),
+	("letterO",       418., 1284.3, 60., 66. - 0.5),
+	("letterP",       489., 1427.3, 48., 65. - 0.5),
+	("letterQ",       418., 1205.3, 60., 79. - 0.5),
+	("letterR",       478., 1249.3, 51., 65. - 0.5),
+	("letterS",       511., 1899.3, 46., 66. - 0.5),
+	("letterT",       460., 1043.3, 52., 64. - 0.5),
+	("letterU",       485., 1757.3, 51., 66. - 0.5),
+	("letterV",       400., 913.3, 61., 64. - 0.5),
+	("letterW",       136., 1320.3, 76., 64. - 0.5),
+	("letterX",       418., 1409.3, 58., 64. - 0.5),
+	("letterY",       432., 1473.3, 57., 64. - 0.5),
+	("letterZ",       486., 1613.3, 50., 64. - 0.5),
+	("medalBronze",   0., 1400., 114., 119.),
+	("medalGold",     0., 1519., 114., 119.),
+	("medalSilver",   0., 1638., 114., 119.),
+	("number0",       432., 1743., 53., 78.),
+	("number1",       512., 1093., 37., 76.),
+	("number2",       477., 1350., 51., 77.),
+	("number3",       485., 1679., 51., 78.),
+	("number4",       432., 1537., 55., 76.),
+	("number5",       485., 1823., 50., 76.),
+	("number6",       432., 1885., 53., 77.),
+	("number7",       478., 1173., 51., 76.),
+	("number8",       461., 899., 51., 78.),
+	("number9",       458., 1962., 53., 77.),
+	("planeBlue1",    330., 1371., 88. - 0.4, 73. - 0.4),
+	("planeBlue2",    372., 1132., 88. - 0.4, 73. - 0.4),
+	("planeBlue3",    222., 1562., 88. - 0.4, 73. - 0.4),
+	("planeGreen1",   114., 1639., 88., 73.),
+	("planeGreen2",   216., 1951., 88., 73.),
+	("planeGreen3",   222., 1489., 88., 73.),
+	("planeRed1",     216., 1878., 88., 73.),
+	("planeRed2",     372., 1059., 88., 73.),
+	("planeRed3",     372., 986., 88., 73.),
+	("planeYellow1",  304., 1967., 88., 73.),
+	("planeYellow2",  330., 1298., 88., 73.),
+	("planeYellow3",  330., 1225., 88., 73.),
+	("puffLarge",     114., 1712., 42., 35.),
+	("puffSmall",     196., 1250., 25., 21.),
+	("rock",          114., 1400.4, 108. - 0.3, 239. - 0.4),
+	("rockDown",      324., 1489., 108. - 0.4, 239.),
+	("rockGrass",     0., 1757., 108. - 0.3, 239.),
+	("rockGrassDown", 264.3, 986., 108. - 0.4, 239. - 0.5),
+	("rockIce",       216., 1639., 108. - 0.3, 239.),
+	("rockIceDown",   222., 1250., 108. - 0.4, 239. - 0.5),
+	("rockSnow",      324., 1728., 108. - 0.3, 239.),
+	("rockSnowDown",  108., 1757., 108. - 0.4, 239. - 0.5),
+	("starBronze",    170., 1996., 39., 37.),
+	("starGold",      369., 1444., 39., 37.),
+	("starSilver",    330., 1444., 39., 37.),
+	("tap",           156., 1712., 40., 40.),
+	("tapLeft",       0., 1996., 85., 42.),
+	("tapRight",      85., 1996., 85., 42.),
+	("tapTick",       418., 1350., 59., 59. - 0.3),
+	("textGameOver",  0., 835.4, 412., 78. - 0.4),
+	("textGetReady",  0., 913., 400., 73.),
+];
 
 pub struct AssetsPlugin;
 
@@ -7,7 +95,9 @@ impl Plugin for AssetsPlugin {
 	fn build(&self, app: &mut App) {
 		app
 			.insert_resource(SpriteSheet::default())
+			.insert_resource(SpriteHulls::default())
 			.add_startup_system(load_sprite_sheet.in_base_set(StartupSet::PreStartup))
+			.add_system(build_sprite_hulls)
 		;
 	}
 }
@@ -28,9 +118,46 @@ impl SpriteSheet {
 	}
 }
 
+/// Convex collision hulls for each sprite, built from the sprite sheet's alpha
+/// channel once it has loaded
+#[derive(Resource, Default)]
+pub struct SpriteHulls (HashMap<String, Vec<Vec<Vec2>>>);
+
+impl SpriteHulls {
+	/// Traces the hulls of every sprite on the decoded sheet
+	pub fn build (sheet : &Image) -> Self {
+		let width = sheet.texture_descriptor.size.width as usize;
+		
+		SpriteHulls(SPRITES.iter().map(|&(name, x, y, w, h)| (
+			name.to_string(),
+			alpha_hulls(&sheet.data, width, sprite_rect(x, y, w, h), HULL_BAND),
+		)).collect())
+	}
+	
+	pub fn get (&self, name : &str) -> Option<&Vec<Vec<Vec2>>> {
+		self.0.get(name)
+	}
+}
+
 // Systems
 // =========================================================================
 
+fn build_sprite_hulls (
+	mut reader : EventReader<AssetEvent<Image>>,
+	images : Res<Assets<Image>>,
+	sprite_sheet : Res<SpriteSheet>,
+	mut hulls : ResMut<SpriteHulls>,
+) {
+	for event in reader.iter() {
+		let AssetEvent::Created { handle } = event else { continue; };
+		if *handle != sprite_sheet.texture_handle { continue; }
+		
+		if let Some(image) = images.get(handle) {
+			*hulls = SpriteHulls::build(image);
+		}
+	}
+}
+
 fn load_sprite_sheet (
 	asset_server : Res<AssetServer>,
 	mut texture_atlases : ResMut<Assets<TextureAtlas>>,
@@ -42,104 +169,31 @@ fn load_sprite_sheet (
 		Vec2::new(1024., 2048.),
 	);
 	
-	let mut sprites = HashMap::new();
-	
-	let mut add = |
-		name : &str,
-		x : f32,
-		y : f32,
-		width : f32,
-		height : f32,
-	| {
-		sprites.insert(name.to_string(), texture_atlas.add_texture(Rect {
-			min: Vec2::new(x, y),
-			max: Vec2::new(x + width, y + height),
-		}));
-	};
-	
-	add("UIbg", 0., 986., 264., 264.);
-	add("background", 0., 355., 800., 480.);
-	add("buttonLarge", 0., 1250., 196., 70.);
-	add("buttonSmall", 0., 1320., 136., 80.);
-	add("groundDirt", 0., 0., 808., 71.);
-	add("groundGrass", 0., 142.3, 808., 71.);
-	add("groundIce", 0., 71.3, 808., 71.);
-	add("groundRock", 0., 284.3, 808., 71.);
-	add("groundSnow", 0., 213.3, 808., 71.);
-	add("letterA", 412., 835.3, 61., 64. - 0.5);
-	add("letterB", 487., 1537.3, 50., 66. - 0.5);
-	add("letterC", 460., 977.3, 52., 66. - 0.5);
-	add("letterD", 432., 1613.3, 54., 66. - 0.5);
-	add("letterE", 511., 1965.3, 45., 64. - 0.5);
-	add("letterF", 512., 963.3, 44., 64. - 0.5);
-	add("letterG", 460., 1107.3, 52., 66. - 0.5);
-	add("letterH", 473., 835.3, 51., 64. - 0.5);
-	add("letterI", 524., 835.3, 22., 64. - 0.5);
-	add("letterJ", 512., 1027.3, 42., 66. - 0.5);
-	add("letterK", 432., 1821.3, 53., 64. - 0.5);
-	add("letterL", 512., 899.3, 44., 64. - 0.5);
-	add("letterM", 392., 1967.3, 66., 64. - 0.5);
-	add("letterN", 432., 1679.3, 53., 64. - 0.5);
-	add("letterO", 418., 1284.3, 60., 66. - 0.5);
-	add("letterP", 489., 1427.3, 48., 65. - 0.5);
-	add("letterQ", 418., 1205.3, 60., 79. - 0.5);
-	add("letterR", 478., 1249.3, 51., 65. - 0.5);
-	add("letterS", 511., 1899.3, 46., 66. - 0.5);
-	add("letterT", 460., 1043.3, 52., 64. - 0.5);
-	add("letterU", 485., 1757.3, 51., 66. - 0.5);
-	add("letterV", 400., 913.3, 61., 64. - 0.5);
-	add("letterW", 136., 1320.3, 76., 64. - 0.5);
-	add("letterX", 418., 1409.3, 58., 64. - 0.5);
-	add("letterY", 432., 1473.3, 57., 64. - 0.5);
-	add("letterZ", 486., 1613.3, 50., 64. - 0.5);
-	add("medalBronze", 0., 1400., 114., 119.);
-	add("medalGold", 0., 1519., 114., 119.);
-	add("medalSilver", 0., 1638., 114., 119.);
-	add("number0", 432., 1743., 53., 78.);
-	add("number1", 512., 1093., 37., 76.);
-	add("number2", 477., 1350., 51., 77.);
-	add("number3", 485., 1679., 51., 78.);
-	add("number4", 432., 1537., 55., 76.);
-	add("number5", 485., 1823., 50., 76.);
-	add("number6", 432., 1885., 53., 77.);
-	add("number7", 478., 1173., 51., 76.);
-	add("number8", 461., 899., 51., 78.);
-	add("number9", 458., 1962., 53., 77.);
-	add("planeBlue1", 330., 1371., 88. - 0.4, 73. - 0.4);
-	add("planeBlue2", 372., 1132., 88. - 0.4, 73. - 0.4);
-	add("planeBlue3", 222., 1562., 88. - 0.4, 73. - 0.4);
-	add("planeGreen1", 114., 1639., 88., 73.);
-	add("planeGreen2", 216., 1951., 88., 73.);
-	add("planeGreen3", 222., 1489., 88., 73.);
-	add("planeRed1", 216., 1878., 88., 73.);
-	add("planeRed2", 372., 1059., 88., 73.);
-	add("planeRed3", 372., 986., 88., 73.);
-	add("planeYellow1", 304., 1967., 88., 73.);
-	add("planeYellow2", 330., 1298., 88., 73.);
-	add("planeYellow3", 330., 1225., 88., 73.);
-	add("puffLarge", 114., 1712., 42., 35.);
-	add("puffSmall", 196., 1250., 25., 21.);
-	add("rock", 114., 1400.4, 108. - 0.3, 239. - 0.4);
-	add("rockDown", 324., 1489., 108. - 0.4, 239.);
-	add("rockGrass", 0., 1757., 108. - 0.3, 239.);
-	add("rockGrassDown", 264.3, 986., 108. - 0.4, 239. - 0.5);
-	add("rockIce", 216., 1639., 108. - 0.3, 239.);
-	add("rockIceDown", 222., 1250., 108. - 0.4, 239. - 0.5);
-	add("rockSnow", 324., 1728., 108. - 0.3, 239.);
-	add("rockSnowDown", 108., 1757., 108. - 0.4, 239. - 0.5);
-	add("starBronze", 170., 1996., 39., 37.);
-	add("starGold", 369., 1444., 39., 37.);
-	add("starSilver", 330., 1444., 39., 37.);
-	add("tap", 156., 1712., 40., 40.);
-	add("tapLeft", 0., 1996., 85., 42.);
-	add("tapRight", 85., 1996., 85., 42.);
-	add("tapTick", 418., 1350., 59., 59. - 0.3);
-	add("textGameOver", 0., 835.4, 412., 78. - 0.4);
-	add("textGetReady", 0., 913., 400., 73.);
+	for &(_, x, y, width, height) in SPRITES {
+		texture_atlas.add_texture(sprite_rect(x, y, width, height));
+	}
 	
 	let texture_atlas_handle = texture_atlases.add(texture_atlas);
 	
-	spritesheet.sprites = sprites;
+	spritesheet.sprites = sprite_indices();
 	spritesheet.handle = texture_atlas_handle;
 	spritesheet.texture_handle = texture_handle;
 }
+
+// Helpers
+// =========================================================================
+
+/// Each sprite's index in the sheet's atlas
+pub fn sprite_indices () -> HashMap<String, usize> {
+	SPRITES.iter()
+		.enumerate()
+		.map(|(index, &(name, ..))| (name.to_string(), index))
+		.collect()
+}
+
+fn sprite_rect (x : f32, y : f32, width : f32, height : f32) -> Rect {
+	Rect {
+		min: Vec2::new(x, y),
+		max: Vec2::new(x + width, y + height),
+	}
+}
@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use crate::Level;
+use crate::obstacle::motion::RockMotion;
+use crate::physics::{apply_flap, CollisionFilter, CollisionKind, FlapInput, FlapInputSet, OBBCollider, PhysicsConfig, PhysicsTransform, step_flight, Velocity};
+use crate::physics::broadphase::Broadphase;
+use crate::physics::geometry::{aabb, bounds, convex_hull, sat};
+
+/// How far ahead the autopilot looks, in physics ticks
+const HORIZON : u32 = 60;
+
+/// Ticks between the points where a searched path can change its mind
+const DECISION_TICKS : u32 = 5;
+
+/// Most ticks the search simulates each physics tick, before it settles for
+/// the best path it has found
+const BUDGET : u32 = 4_000;
+
+/// Plane states closer than this, in pixels and pixels per second, are
+/// treated as the same when searching
+const HEIGHT_STEP   : f32 = 2.;
+const VELOCITY_STEP : f32 = 10.;
+
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.add_startup_system(load_from_args)
+			.edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+				schedule.configure_set(AutopilotSet.in_set(FlapInputSet));
+			})
+			.add_system(
+				fly
+					.in_set(AutopilotSet)
+					.run_if(resource_exists::<Autopilot>())
+					.in_schedule(CoreSchedule::FixedUpdate)
+			)
+		;
+	}
+}
+
+// Sets
+// =========================================================================
+
+/// Where the autopilot decides the tick's `FlapInput`
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AutopilotSet;
+
+// Resources
+// =========================================================================
+
+/// Flies the plane instead of the player while it exists
+#[derive(Resource, Clone)]
+pub struct Autopilot {
+	/// How much the plane's collider is grown while searching, so the bot
+	/// keeps some room between it and the rocks
+	pub margin : f32,
+}
+
+impl Default for Autopilot {
+	fn default () -> Self {
+		Autopilot { margin: 1.2 }
+	}
+}
+
+// Search
+// =========================================================================
+
+/// The plane's state between ticks
+#[derive(Copy, Clone)]
+struct Body {
+	velocity  : Vec2,
+	transform : Transform,
+}
+
+/// A collider ahead of the plane, with its bounds
+struct Shape {
+	min    : Vec2,
+	max    : Vec2,
+	points : Vec<Vec2>,
+	/// How the rock the collider belongs to moves, if it does
+	motion : Option<RockMotion>,
+}
+
+impl Shape {
+	fn new (points : Vec<Vec2>, motion : Option<RockMotion>) -> Self {
+		let (min, max) = bounds(&points);
+		Shape { min, max, points, motion }
+	}
+	
+	/// How far the collider will have moved up on the tick `tick` ticks from
+	/// now, since the broadphase saw it a tick ago
+	fn rise (&self, tick : u32) -> f32 {
+		let Some(rock) = &self.motion else { return 0.; };
+		
+		rock.motion.offset(rock.orientation, rock.ticks + tick)
+			- rock.motion.offset(rock.orientation, rock.ticks.saturating_sub(1))
+	}
+}
+
+/// Looks for the longest way through the colliders ahead by trying both
+/// flapping and not, every few ticks, on a copy of the plane's physics
+struct Search<'a> {
+	config   : &'a PhysicsConfig,
+	collider : OBBCollider,
+	parent   : GlobalTransform,
+	/// Colliders that scroll towards the plane each tick
+	moving   : Vec<Shape>,
+	/// Colliders that stay put, like the ground and ceiling
+	fixed    : Vec<Shape>,
+	/// How far the moving colliders scroll each tick
+	scroll   : f32,
+	delta    : f32,
+	budget   : u32,
+	/// How long the plane survives from states already searched
+	seen     : HashMap<(u32, i32, i32), u32>,
+}
+
+impl Search<'_> {
+	/// Ticks survived flying `flap` for a while from `tick`, then the best
+	/// choices after that, up to `HORIZON`
+	fn survive (&mut self, mut body : Body, tick : u32, flap : bool) -> u32 {
+		for i in 0..DECISION_TICKS {
+			let tick = tick + i;
+			
+			if tick >= HORIZON { return HORIZON; }
+			if self.budget == 0 { return tick; }
+			self.budget -= 1;
+			
+			let input = FlapInput { pressed: flap && i == 0, held: flap };
+			let previous = body.transform;
+			
+			apply_flap(self.config, &input, &mut body.velocity, self.delta);
+			step_flight(self.config, &mut body.velocity, &mut body.transform, self.delta);
+			
+			if self.hits(&previous, &body.transform, tick) {
+				return tick;
+			}
+		}
+		
+		let tick = tick + DECISION_TICKS;
+		
+		let key = (
+			tick,
+			(body.transform.translation.y / HEIGHT_STEP).round() as i32,
+			(body.velocity.y / VELOCITY_STEP).round() as i32,
+		);
+		
+		if let Some(&best) = self.seen.get(&key) {
+			return best;
+		}
+		
+		let mut best = 0;
+		
+		// Not flapping first, since the plane falls most of the time
+		for flap in [false, true] {
+			best = best.max(self.survive(body, tick, flap));
+			
+			if best >= HORIZON { break; }
+		}
+		
+		self.seen.insert(key, best);
+		best
+	}
+	
+	/// Whether the plane hits anything moving from `previous` to `current`
+	/// on the tick `tick` ticks from now
+	fn hits (&self, previous : &Transform, current : &Transform, tick : u32) -> bool {
+		// Swept like the real collision test
+		let points = convex_hull(&[
+			self.collider.points(&self.parent.mul_transform(*previous)),
+			self.collider.points(&self.parent.mul_transform(*current)),
+		].concat());
+		
+		// Moving the plane rather than the rocks it's tested against; the
+		// rocks have already scrolled once this tick by the time we look
+		let scroll = Vec2::new(self.scroll * (tick + 1) as f32, 0.);
+		let scrolled : Vec<_> = points.iter().map(|p| *p + scroll).collect();
+		
+		collides(&points, &self.fixed, tick) || collides(&scrolled, &self.moving, tick)
+	}
+}
+
+fn collides (points : &[Vec2], shapes : &[Shape], tick : u32) -> bool {
+	let (min, max) = bounds(points);
+	
+	shapes.iter().any(|shape| {
+		// Moving the plane down rather than a rising rock up, like scrolling
+		let drop = Vec2::new(0., shape.rise(tick));
+		
+		aabb(min - drop, max - drop, shape.min, shape.max)
+			&& sat(&points.iter().map(|p| *p - drop).collect::<Vec<_>>(), &shape.points).is_some()
+	})
+}
+
+// Systems
+// =========================================================================
+
+/// `--autopilot` lets the bot fly
+fn load_from_args (
+	mut commands : Commands,
+) {
+	if std::env::args().any(|arg| arg == "--autopilot") {
+		commands.insert_resource(Autopilot::default());
+	}
+}
+
+fn fly (
+	autopilot : Res<Autopilot>,
+	mut flap : ResMut<FlapInput>,
+	config : Res<PhysicsConfig>,
+	level : Res<Level>,
+	fixed_time : Res<FixedTime>,
+	broadphase : Res<Broadphase>,
+	player_query : Query<(&Parent, &Velocity, &PhysicsTransform, &OBBCollider, Option<&CollisionFilter>)>,
+	parent_query : Query<&GlobalTransform>,
+	collider_query : Query<(&CollisionKind, Option<&CollisionFilter>, Option<&Parent>)>,
+	rock_query : Query<&RockMotion>,
+) {
+	let Ok((parent, velocity, body, collider, filter)) = player_query.get_single() else { return; };
+	let Ok(parent) = parent_query.get(parent.get()) else { return; };
+	
+	let filter = filter.copied().unwrap_or_default();
+	let delta = fixed_time.period.as_secs_f32();
+	let scroll = level.spawner.speed * delta;
+	
+	let mut search = Search {
+		config: &config,
+		collider: OBBCollider(collider.0 * autopilot.margin, collider.1),
+		parent: *parent,
+		moving: Vec::new(),
+		fixed: Vec::new(),
+		scroll,
+		delta,
+		budget: BUDGET,
+		seen: HashMap::default(),
+	};
+	
+	// Everything the plane could reach before the horizon
+	let x = parent.translation().x;
+	let reach = scroll * HORIZON as f32 + collider.0.max_element() * 2.;
+	
+	for proxy in broadphase.query(Vec2::new(x - reach, f32::MIN), Vec2::new(x + reach, f32::MAX)) {
+		let Ok((kind, other, rock)) = collider_query.get(proxy.entity) else { continue; };
+		let other = other.copied().unwrap_or_default();
+		
+		if !filter.interacts(&other) || other.sensor { continue; }
+		
+		match kind {
+			CollisionKind::Rock => {
+				let motion = rock.and_then(|rock| rock_query.get(rock.get()).ok()).copied();
+				search.moving.push(Shape::new(proxy.points.clone(), motion));
+			}
+			CollisionKind::Ground | CollisionKind::Ceiling => search.fixed.push(Shape::new(proxy.points.clone(), None)),
+		}
+	}
+	
+	let body = Body {
+		velocity: velocity.0,
+		transform: body.current,
+	};
+	
+	// Only flap when holding off would do worse
+	let glide = search.survive(body, 0, false);
+	let climb = if glide < HORIZON { search.survive(body, 0, true) } else { 0 };
+	let flapping = climb > glide;
+	
+	flap.pressed = flapping;
+	flap.held = flapping;
+}
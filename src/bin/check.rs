@@ -0,0 +1,174 @@
+//! Generates levels the way the game does and checks that every obstacle in
+//! them can be flown through, using the physics tuning in
+//! `assets/plane.physics.ron` and the rocks in `assets/rocks.obstacles.ron`.
+//! Prints a line of JSON per level, and fails if any obstacle can't be
+//! reached.
+//!
+//! ```sh
+//! cargo run --bin check -- --seed 42 --levels 10
+//! cargo run --bin check -- --seed 0 --seeds 1000 --levels 15
+//! ```
+
+use std::process::ExitCode;
+use serde::Serialize;
+use tappy_plane::{BASE_LEVEL, Level};
+use tappy_plane::obstacle::{layout, ObstacleCatalogue, place_pattern, SpawnSchedule};
+use tappy_plane::obstacle::reach::CourseCheck;
+use tappy_plane::physics::{PhysicsConfig, TIMESTEP};
+use tappy_plane::rng::{GameRng, Stream};
+
+const CONFIG    : &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/plane.physics.ron");
+const CATALOGUE : &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/rocks.obstacles.ron");
+
+const USAGE : &str = "usage: check [--seed <u64>] [--seeds <count>] [--levels <count>]";
+
+// Options
+// =========================================================================
+
+struct Options {
+	seed   : u64,
+	seeds  : u64,
+	levels : usize,
+}
+
+impl Options {
+	fn from_args () -> Result<Self, String> {
+		let mut options = Options { seed: 0, seeds: 1, levels: 10 };
+		let mut args = std::env::args().skip(1);
+		
+		while let Some(arg) = args.next() {
+			let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+			
+			match arg.as_str() {
+				"--seed" => options.seed = value()?.parse().map_err(|e| format!("bad seed: {}", e))?,
+				"--seeds" => options.seeds = value()?.parse().map_err(|e| format!("bad seed count: {}", e))?,
+				"--levels" => options.levels = value()?.parse().map_err(|e| format!("bad level count: {}", e))?,
+				_ => return Err(format!("unknown argument {}", arg)),
+			}
+		}
+		
+		Ok(options)
+	}
+}
+
+// Report
+// =========================================================================
+
+#[derive(Serialize)]
+struct LevelReport {
+	seed       : u64,
+	level      : usize,
+	/// The index of each obstacle the plane can't get through
+	impossible : Vec<usize>,
+	obstacles  : Vec<Spawned>,
+}
+
+#[derive(Serialize)]
+struct Spawned {
+	pattern  : String,
+	moving   : bool,
+	tick     : u32,
+	passable : bool,
+}
+
+// Main
+// =========================================================================
+
+fn main () -> ExitCode {
+	let options = match Options::from_args() {
+		Ok(options) => options,
+		Err(e) => {
+			eprintln!("{}\n{}", e, USAGE);
+			return ExitCode::FAILURE;
+		}
+	};
+	
+	let (config, catalogue) = match read_assets() {
+		Ok(assets) => assets,
+		Err(e) => {
+			eprintln!("{}", e);
+			return ExitCode::FAILURE;
+		}
+	};
+	
+	let mut passable = true;
+	
+	for seed in options.seed..options.seed.saturating_add(options.seeds) {
+		let rng = GameRng::new(seed);
+		let mut level = BASE_LEVEL;
+		
+		for _ in 0..options.levels {
+			// Later levels shrink the spawn interval past nothing
+			if level.spawner.interval < TIMESTEP {
+				eprintln!("level {} spawns obstacles faster than the physics ticks", level.index);
+				return ExitCode::FAILURE;
+			}
+			
+			let report = check_level(&config, &catalogue, &rng, &level, seed);
+			passable &= report.impossible.is_empty();
+			println!("{}", serde_json::to_string(&report).unwrap());
+			
+			level = level.next(&rng);
+		}
+	}
+	
+	if passable { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+fn read_assets () -> Result<(PhysicsConfig, ObstacleCatalogue), String> {
+	let read = |path : &str| std::fs::read(path).map_err(|e| format!("couldn't read {}: {}", path, e));
+	
+	let config = ron::de::from_bytes(&read(CONFIG)?)
+		.map_err(|e| format!("couldn't read {}: {}", CONFIG, e))?;
+	
+	let catalogue = ObstacleCatalogue::from_bytes(&read(CATALOGUE)?)
+		.map_err(|e| format!("couldn't read {}: {}", CATALOGUE, e))?;
+	
+	Ok((config, catalogue))
+}
+
+/// Lays out the level's obstacles from the same stream the game draws them
+/// from, checking each in turn
+fn check_level (
+	config : &PhysicsConfig,
+	catalogue : &ObstacleCatalogue,
+	rng : &GameRng,
+	level : &Level,
+	seed : u64,
+) -> LevelReport {
+	let mut obstacles = rng.level(level.index, Stream::Obstacles);
+	let mut check = CourseCheck::new(config, catalogue, &level.spawner);
+	let mut schedule = SpawnSchedule::new(level);
+	let mut spawned = Vec::new();
+	
+	while let Some(tick) = schedule.next() {
+		let pattern = catalogue.pick_pattern(&mut obstacles, level.index);
+		let motion = catalogue.pick_motion(&mut obstacles, level.theme, level.index);
+		let placement = place_pattern(&mut obstacles, pattern, motion, &level.spawner, &mut check, tick);
+		let passable = placement.passable;
+		
+		// The rocks draw from the same stream, so lay them out to keep in step
+		let layout = layout(&mut obstacles, catalogue, level.theme, placement);
+		schedule.wait(&level.spawner, layout.width());
+		
+		spawned.push(Spawned {
+			pattern: pattern.name.clone(),
+			moving: motion.is_some(),
+			tick,
+			passable,
+		});
+	}
+	
+	let impossible = spawned.iter()
+		.enumerate()
+		.filter(|(_, spawned)| !spawned.passable)
+		.map(|(index, _)| index)
+		.collect();
+	
+	LevelReport {
+		seed,
+		level: level.index,
+		impossible,
+		obstacles: spawned,
+	}
+}
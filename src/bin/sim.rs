@@ -0,0 +1,339 @@
+//! Plays a level of Tappy Plane without a window, audio or rendering, and
+//! prints how it went as JSON. Meant for CI and balance sweeps on machines
+//! without a GPU.
+//!
+//! ```sh
+//! cargo run --bin sim -- --seed 42 --level 3 --autopilot
+//! cargo run --bin sim -- --seed 42 --inputs flaps.txt
+//! cargo run --bin sim -- --replay last.replay
+//! ```
+//!
+//! An input script has the physics tick of a flap on each line. Blank lines
+//! and lines starting with `#` are skipped.
+//!
+//! Assets are read from `--assets <dir>`, or `TAPPY_PLANE_ASSETS`, falling
+//! back to the checkout the binary was built from. Exits with a failure if
+//! the plane doesn't finish the level, or anything can't be loaded.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use bevy::asset::{Asset, LoadState};
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::prelude::*;
+use bevy::render::texture::{CompressedImageFormats, ImageType};
+use bevy::time::TimeUpdateStrategy;
+use bevy::utils::Instant;
+use serde::Serialize;
+use tappy_plane::{AppState, BASE_LEVEL, DistanceTravelled, GameState, Level};
+use tappy_plane::actions::Action;
+use tappy_plane::assets::{SpriteHulls, SpriteSheet, sprite_indices};
+use tappy_plane::autopilot::{Autopilot, AutopilotPlugin};
+use tappy_plane::level::{Crash, GROUND_POSITION, LevelPlugin, PLANE_OFFSET, PLANE_START, ceiling, ground_collider, plane_body};
+use tappy_plane::obstacle::{ObstacleCatalogue, ObstacleCatalogueHandle, ObstaclePlugin};
+use tappy_plane::physics::{CollisionKind, FlapInput, FlapInputSet, PhysicsConfigHandle, PhysicsPlugin, PhysicsTick};
+use tappy_plane::replay::{Playback, Replay, ReplayInput, play_back};
+use tappy_plane::rng::GameRng;
+use tappy_plane::scenes::{GameRoot, PlaneRoot};
+
+/// Where the game's assets are, unless `--assets` or `TAPPY_PLANE_ASSETS`
+/// say otherwise
+const DEFAULT_ASSETS : &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets");
+
+/// Gives up on a level that hasn't ended after this many ticks
+const MAX_TICKS : u32 = 60 * 60 * 10;
+
+const USAGE : &str = "\
+usage: sim [--assets <dir>] [--seed <u64>] [--level <index>] (--inputs <file> | --replay <file> | --autopilot)";
+
+// Options
+// =========================================================================
+
+/// Where the flap input comes from
+enum Pilot {
+	Script(Vec<ReplayInput>),
+	Autopilot,
+}
+
+struct Options {
+	assets : PathBuf,
+	seed   : u64,
+	level  : Level,
+	pilot  : Pilot,
+}
+
+impl Options {
+	fn from_args () -> Result<Self, String> {
+		let mut assets = std::env::var_os("TAPPY_PLANE_ASSETS")
+			.map_or_else(|| DEFAULT_ASSETS.into(), PathBuf::from);
+		let mut seed = 0;
+		let mut index = BASE_LEVEL.index;
+		let mut pilot = None;
+		let mut replay = None;
+		
+		let mut args = std::env::args().skip(1);
+		
+		while let Some(arg) = args.next() {
+			let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+			
+			match arg.as_str() {
+				"--assets" => assets = value()?.into(),
+				"--seed" => seed = value()?.parse().map_err(|e| format!("bad seed: {}", e))?,
+				"--level" => index = value()?.parse().map_err(|e| format!("bad level: {}", e))?,
+				"--inputs" => pilot = Some(Pilot::Script(read_script(&value()?)?)),
+				"--replay" => replay = Some(read_replay(&value()?)?),
+				"--autopilot" => pilot = Some(Pilot::Autopilot),
+				_ => return Err(format!("unknown argument {}", arg)),
+			}
+		}
+		
+		// Bevy resolves relative paths against the executable, not where
+		// the sim is run from
+		let assets = std::env::current_dir()
+			.map_err(|e| format!("couldn't find the working directory: {}", e))?
+			.join(assets);
+		
+		// A replay brings its own seed and level
+		if let Some(replay) = replay {
+			return Ok(Options {
+				assets,
+				seed: replay.run_seed,
+				level: replay.level,
+				pilot: Pilot::Script(replay.inputs),
+			});
+		}
+		
+		if index < BASE_LEVEL.index {
+			return Err(format!("levels start at {}", BASE_LEVEL.index));
+		}
+		
+		let rng = GameRng::new(seed);
+		let mut level = BASE_LEVEL;
+		
+		while level.index < index {
+			level = level.next(&rng);
+		}
+		
+		Ok(Options {
+			assets,
+			seed,
+			level,
+			pilot: pilot.ok_or("pick --inputs, --replay or --autopilot")?,
+		})
+	}
+}
+
+fn read_script (path : &str) -> Result<Vec<ReplayInput>, String> {
+	let script = std::fs::read_to_string(path)
+		.map_err(|e| format!("couldn't read {}: {}", path, e))?;
+	
+	let mut inputs = script.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.map(|line| line.parse().map(|tick| ReplayInput {
+			tick,
+			pressed: true,
+			held: false,
+		}).map_err(|_| format!("bad tick in {}: {}", path, line)))
+		.collect::<Result<Vec<_>, _>>()?;
+	
+	inputs.sort_by_key(|input| input.tick);
+	Ok(inputs)
+}
+
+fn read_replay (path : &str) -> Result<Replay, String> {
+	let bytes = std::fs::read(path)
+		.map_err(|e| format!("couldn't read {}: {}", path, e))?;
+	
+	Replay::from_bytes(&bytes).map_err(|e| format!("couldn't read {}: {}", path, e))
+}
+
+// Resources
+// =========================================================================
+
+/// What gets printed once the level is over
+#[derive(Serialize)]
+struct SimResult {
+	seed        : u64,
+	level       : usize,
+	survived    : bool,
+	distance    : f32,
+	ticks       : u32,
+	death_cause : Option<&'static str>,
+}
+
+// Main
+// =========================================================================
+
+fn main () -> ExitCode {
+	let options = match Options::from_args() {
+		Ok(options) => options,
+		Err(e) => {
+			eprintln!("{}\n{}", e, USAGE);
+			return ExitCode::FAILURE;
+		}
+	};
+	
+	let mut app = App::new();
+	
+	app
+		.add_plugins(MinimalPlugins)
+		.add_plugin(TransformPlugin)
+		.add_plugin(HierarchyPlugin)
+		.add_plugin(AssetPlugin {
+			asset_folder: options.assets.to_string_lossy().into(),
+			..default()
+		})
+		.insert_resource(TimeUpdateStrategy::ManualInstant(Instant::now()))
+		.insert_resource(options.level.clone())
+		.insert_resource(DistanceTravelled(0.))
+		.insert_resource(GameRng::new(options.seed))
+		.init_resource::<Input<Action>>()
+		.add_state::<AppState>()
+		.add_state::<GameState>()
+		.add_plugin(PhysicsPlugin)
+		.add_plugin(ObstaclePlugin)
+		.add_plugin(LevelPlugin)
+		.add_plugin(AutopilotPlugin)
+		.add_system(
+			play_back
+				.in_set(FlapInputSet)
+				.run_if(resource_exists::<Playback>())
+				.in_schedule(CoreSchedule::FixedUpdate)
+		)
+	;
+	
+	// Each update is tiny, so waking worker threads costs more than it saves
+	for schedule in [CoreSchedule::Main, CoreSchedule::FixedUpdate] {
+		app.edit_schedule(schedule, |schedule| {
+			schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+		});
+	}
+	
+	match options.pilot {
+		Pilot::Script(inputs) => app.insert_resource(Playback::new(inputs)),
+		Pilot::Autopilot => app.insert_resource(Autopilot::default()),
+	};
+	
+	if let Err(e) = load_assets(&mut app, &options.assets) {
+		eprintln!("{}", e);
+		return ExitCode::FAILURE;
+	}
+	
+	spawn_level(&mut app.world);
+	
+	// One update to place everything, as the game does while the level
+	// animates in
+	app.world.resource_mut::<NextState<AppState>>().set(AppState::Game);
+	app.world.resource_mut::<NextState<GameState>>().set(GameState::Enter);
+	step(&mut app);
+	
+	app.world.resource_mut::<NextState<GameState>>().set(GameState::Play);
+	
+	loop {
+		step(&mut app);
+		
+		let state = app.world.resource::<State<GameState>>().0;
+		let ticks = app.world.resource::<PhysicsTick>().0;
+		
+		if matches!(state, GameState::Dead | GameState::Exit) || ticks >= MAX_TICKS {
+			break;
+		}
+	}
+	
+	let world = &app.world;
+	let crash = world.resource::<Crash>().0;
+	
+	let result = SimResult {
+		seed: options.seed,
+		level: options.level.index,
+		survived: world.resource::<State<GameState>>().0 == GameState::Exit,
+		distance: world.resource::<DistanceTravelled>().0,
+		ticks: world.resource::<PhysicsTick>().0,
+		death_cause: crash.map(|kind| match kind {
+			CollisionKind::Ground => "ground",
+			CollisionKind::Ceiling => "ceiling",
+			CollisionKind::Rock => "rock",
+		}),
+	};
+	
+	println!("{}", serde_json::to_string(&result).unwrap());
+	
+	if result.survived { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+/// Waits for the physics tuning and obstacle catalogue to load, and traces
+/// the rock hulls from the sprite sheet the same way the game does
+fn load_assets (app : &mut App, assets : &Path) -> Result<(), String> {
+	let handle = app.world.resource::<PhysicsConfigHandle>().0.clone();
+	let config = wait_for(app, handle, "plane.physics.ron")?;
+	app.world.insert_resource(config);
+	
+	let handle = app.world.resource::<ObstacleCatalogueHandle>().0.clone();
+	let catalogue = wait_for(app, handle, "rocks.obstacles.ron")?;
+	app.world.insert_resource(catalogue);
+	
+	let path = assets.join("sheet.png");
+	let bytes = std::fs::read(&path).map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+	
+	let sheet = Image::from_buffer(
+		&bytes,
+		ImageType::Extension("png"),
+		CompressedImageFormats::NONE,
+		true,
+	).map_err(|e| format!("couldn't decode {}: {}", path.display(), e))?;
+	
+	app.world.insert_resource(SpriteHulls::build(&sheet));
+	app.world.insert_resource(SpriteSheet {
+		sprites: sprite_indices(),
+		..default()
+	});
+	
+	Ok(())
+}
+
+/// Updates the app until the asset behind `handle` has loaded
+fn wait_for<T : Asset + Clone> (app : &mut App, handle : Handle<T>, name : &str) -> Result<T, String> {
+	loop {
+		step(app);
+		
+		match app.world.resource::<AssetServer>().get_load_state(&handle) {
+			LoadState::Loaded => return Ok(app.world.resource::<Assets<T>>().get(&handle).unwrap().clone()),
+			LoadState::Failed => return Err(format!("couldn't load {}", name)),
+			_ => std::thread::yield_now(),
+		}
+	}
+}
+
+/// Updates the app with the clock moved on by exactly one physics tick,
+/// however fast it runs
+fn step (app : &mut App) {
+	let period = app.world.resource::<FixedTime>().period;
+	let mut strategy = app.world.resource_mut::<TimeUpdateStrategy>();
+	
+	if let TimeUpdateStrategy::ManualInstant(instant) = &mut *strategy {
+		*instant += period;
+	}
+	
+	app.update();
+}
+
+/// The colliders of the game scene, without anything to look at
+fn spawn_level (world : &mut World) {
+	world.spawn((GameRoot, TransformBundle::default()));
+	world.spawn(ceiling());
+	
+	world.spawn((
+		TransformBundle::from_transform(Transform::from_translation(GROUND_POSITION)),
+		ground_collider(),
+	));
+	
+	world.spawn((
+		PlaneRoot,
+		TransformBundle::from_transform(Transform::from_translation(PLANE_START)),
+	)).with_children(|parent| {
+		parent.spawn((
+			TransformBundle::from_transform(Transform::from_translation(PLANE_OFFSET)),
+			plane_body(),
+		));
+	});
+}
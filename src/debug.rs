@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 use bevy_prototype_debug_lines::{DebugLines, DebugLinesPlugin, DebugShapes};
-use crate::physics::{AABBCollider, SATCollider};
+use crate::physics::{AABBCollider, CollisionEvent, CollisionFilter, OBBCollider, SATCollider};
 
 pub struct DebugPlugin;
 
@@ -9,7 +9,9 @@ impl Plugin for DebugPlugin {
 		app
 			.add_plugin(DebugLinesPlugin::default())
 			.add_system(visualise_aabb_colliders)
+			.add_system(visualise_obb_colliders)
 			.add_system(visualise_sat_colliders)
+			.add_system(visualise_contacts)
 		;
 	}
 }
@@ -18,24 +20,42 @@ impl Plugin for DebugPlugin {
 // =========================================================================
 
 fn visualise_aabb_colliders(
-	query : Query<(&GlobalTransform, &AABBCollider)>,
+	query : Query<(&GlobalTransform, &AABBCollider, Option<&CollisionFilter>)>,
 	mut shapes : ResMut<DebugShapes>,
 ) {
-	for (transform, collider) in &query {
+	for (transform, collider, filter) in &query {
 		shapes
 			.rect()
 			.position(transform.translation() + collider.1.unwrap_or(Vec2::ZERO).extend(0.))
 			.size(collider.0)
-			.color(Color::RED)
+			.color(colour(filter))
 		;
 	}
 }
 
+fn visualise_obb_colliders(
+	query : Query<(&GlobalTransform, &OBBCollider, Option<&CollisionFilter>)>,
+	mut lines : ResMut<DebugLines>,
+) {
+	for (transform, collider, filter) in &query {
+		let points = collider.points(transform);
+		
+		for a in 0..points.len() {
+			lines.line_colored(
+				points[a].extend(0.),
+				points[(a + 1) % points.len()].extend(0.),
+				0.,
+				colour(filter),
+			);
+		}
+	}
+}
+
 fn visualise_sat_colliders(
-	query : Query<(&GlobalTransform, &SATCollider)>,
+	query : Query<(&GlobalTransform, &SATCollider, Option<&CollisionFilter>)>,
 	mut lines : ResMut<DebugLines>,
 ) {
-	for (transform, collider) in &query {
+	for (transform, collider, filter) in &query {
 		let t = transform.translation().truncate();
 		
 		for a in 0..collider.0.len() {
@@ -46,8 +66,33 @@ fn visualise_sat_colliders(
 				(pa + t).extend(0.),
 				(pb + t).extend(0.),
 				0.,
-				Color::RED,
+				colour(filter),
 			);
 		}
 	}
 }
+
+fn visualise_contacts(
+	mut reader : EventReader<CollisionEvent>,
+	mut lines : ResMut<DebugLines>,
+) {
+	for event in reader.iter() {
+		let contact = event.contact;
+		
+		debug!(
+			"{:?} hit {:?} ({:?}), {} deep",
+			event.player, event.other, event.kind, contact.depth,
+		);
+		let point = contact.point.extend(0.);
+		
+		lines.line_colored(point, point + (contact.normal * 20.).extend(0.), 1., Color::BLUE);
+		lines.line_colored(point, point + contact.mtv.extend(0.), 1., Color::YELLOW);
+	}
+}
+
+// Helpers
+// =========================================================================
+
+fn colour (filter : Option<&CollisionFilter>) -> Color {
+	if filter.is_some_and(|f| f.sensor) { Color::GREEN } else { Color::RED }
+}
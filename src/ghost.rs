@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{GameState, Level};
+use crate::assets::SpriteSheet;
+use crate::physics::{FlapInputSet, PhysicsSet, PhysicsTick, PhysicsTransform};
+use crate::replay::Playback;
+use crate::rng::{GameRng, level_seed};
+use crate::scenes::{Attract, PlaneRoot};
+use crate::sprite_animation::{SpriteAnimationIndices, SpriteAnimationTimer};
+use crate::storage::Storage;
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.init_resource::<GhostPaths>()
+			.add_systems((load_ghost, spawn_ghost).chain().in_schedule(OnEnter(GameState::Enter)))
+			// At the start of the tick, before the plane moves, so the path
+			// holds where it was after each tick so far
+			.add_system(
+				record_path
+					.in_set(PhysicsSet)
+					.before(FlapInputSet)
+					.in_schedule(CoreSchedule::FixedUpdate)
+			)
+			.add_system(fly_ghost.in_set(OnUpdate(GameState::Play)))
+			.add_system(hide_ghost.in_schedule(OnEnter(GameState::Dead)))
+			// The autopilot's demo runs and replays played back aren't the
+			// player's to beat
+			.add_system(
+				save_ghost
+					.run_if(not(resource_exists::<Attract>()))
+					.run_if(not(resource_exists::<Playback>()))
+					.in_schedule(OnEnter(GameState::Dead))
+			)
+			.add_system(
+				save_ghost
+					.run_if(not(resource_exists::<Attract>()))
+					.run_if(not(resource_exists::<Playback>()))
+					.in_schedule(OnEnter(GameState::Exit))
+			)
+		;
+	}
+}
+
+// Structs
+// =========================================================================
+
+/// The plane's position and tilt (radians) after each physics tick
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct GhostPath (Vec<[f32; 3]>);
+
+/// The best path for a level, saved under one key per level so ghosts from
+/// other seeds are replaced rather than piling up
+#[derive(Serialize, Deserialize)]
+struct SavedGhost {
+	/// `level_seed` of the run the path was flown in
+	seed : u64,
+	path : GhostPath,
+}
+
+// Resources
+// =========================================================================
+
+/// The path flown this attempt, and the best one for the level
+#[derive(Resource, Default)]
+struct GhostPaths {
+	/// Storage key for this level
+	key     : String,
+	/// Seed of this level, to check the saved path against
+	seed    : u64,
+	current : GhostPath,
+	best    : Option<GhostPath>,
+}
+
+// Components
+// =========================================================================
+
+#[derive(Component)]
+struct Ghost;
+
+// Systems
+// =========================================================================
+
+fn load_ghost (
+	mut paths : ResMut<GhostPaths>,
+	level : Res<Level>,
+	rng : Res<GameRng>,
+	storage : Res<Storage>,
+) {
+	let key = format!("ghost-{}", level.index);
+	let seed = level_seed(rng.run_seed(), level.index);
+	
+	// A path flown on another seed is for a different course
+	let best = storage.load::<SavedGhost>(&key)
+		.filter(|saved| saved.seed == seed)
+		.map(|saved| saved.path);
+	
+	*paths = GhostPaths {
+		best,
+		key,
+		seed,
+		current: GhostPath::default(),
+	};
+}
+
+fn spawn_ghost (
+	mut commands : Commands,
+	sprite_sheet : Res<SpriteSheet>,
+	paths : Res<GhostPaths>,
+	root_query : Query<Entity, With<PlaneRoot>>,
+) {
+	let (Some(best), Ok(root)) = (&paths.best, root_query.get_single()) else { return; };
+	let Some(&[x, y, _]) = best.0.first() else { return; };
+	
+	let mut sprite = sprite_sheet.get("planeBlue1");
+	sprite.color = Color::rgba(1., 1., 1., 0.4);
+	
+	commands.entity(root).with_children(|commands| {
+		commands.spawn((
+			Ghost,
+			SpriteSheetBundle {
+				texture_atlas: sprite_sheet.handle.clone(),
+				sprite,
+				// Just behind the plane
+				transform: Transform::from_xyz(x, y, -0.1),
+				..default()
+			},
+			SpriteAnimationIndices::new(vec![
+				sprite_sheet.get("planeBlue1").index,
+				sprite_sheet.get("planeBlue2").index,
+				sprite_sheet.get("planeBlue3").index,
+			]),
+			SpriteAnimationTimer(Timer::from_seconds(0.04, TimerMode::Repeating)),
+		));
+	});
+}
+
+fn record_path (
+	mut paths : ResMut<GhostPaths>,
+	query : Query<&PhysicsTransform>,
+) {
+	let Ok(body) = query.get_single() else { return; };
+	let transform = &body.current;
+	
+	paths.current.0.push([
+		transform.translation.x,
+		transform.translation.y,
+		transform.rotation.to_euler(EulerRot::XYZ).2,
+	]);
+}
+
+/// Moves the ghost along the best path in step with the physics ticks,
+/// blending between them like the plane does
+fn fly_ghost (
+	mut query : Query<(&mut Transform, &mut Visibility), With<Ghost>>,
+	paths : Res<GhostPaths>,
+	tick : Res<PhysicsTick>,
+	fixed_time : Res<FixedTime>,
+) {
+	let Some(best) = &paths.best else { return; };
+	let Ok((mut transform, mut visibility)) = query.get_single_mut() else { return; };
+	
+	let index = tick.0 as usize;
+	
+	// The ghost crashed here, or finished the level
+	let Some(&[x, y, angle]) = best.0.get(index) else {
+		*visibility = Visibility::Hidden;
+		return;
+	};
+	
+	let [px, py, pangle] = best.0[index.saturating_sub(1)];
+	let alpha = fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32();
+	
+	transform.translation.x = px + (x - px) * alpha;
+	transform.translation.y = py + (y - py) * alpha;
+	transform.rotation = Quat::from_rotation_z(pangle + (angle - pangle) * alpha);
+}
+
+fn hide_ghost (
+	mut query : Query<&mut Visibility, With<Ghost>>,
+) {
+	for mut visibility in &mut query {
+		*visibility = Visibility::Hidden;
+	}
+}
+
+/// Keeps whichever path got further, replacing any saved for another seed
+fn save_ghost (
+	mut paths : ResMut<GhostPaths>,
+	storage : Res<Storage>,
+) {
+	let best_len = paths.best.as_ref().map_or(0, |best| best.0.len());
+	if paths.current.0.len() <= best_len { return; }
+	
+	let path = std::mem::take(&mut paths.current);
+	let saved = SavedGhost { seed: paths.seed, path };
+	storage.save(&paths.key, &saved);
+	paths.best = Some(saved.path);
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use crate::{AppState, DIST_PER_SECOND, DistanceTravelled, GameState, Level, SCREEN_HEIGHT, SCREEN_WIDTH, z};
+use crate::obstacle::{ObstacleCatalogue, SpawnTimer};
+use crate::obstacle::reach::CourseCheck;
+use crate::physics::{AABBCollider, AngularVelocity, CollisionEvent, CollisionFilter, CollisionKind, FlapInputSet, layer, OBBCollider, PhysicsConfig, PhysicsSet, PhysicsTransform, Velocity};
+use crate::rng::GameRng;
+
+/// Where the plane flies from once the level starts
+pub const PLANE_START : Vec3 = Vec3::new(SCREEN_WIDTH * -0.2, 0., z::PLANE);
+
+/// The plane body's offset from its root
+pub const PLANE_OFFSET : Vec3 = Vec3::new(88. * -0.5, 73. * 0.5, 0.);
+
+/// Size of the plane's collider, and how far it sits from the body's centre
+pub const PLANE_COLLIDER : Vec2 = Vec2::new(80. * 0.6, 73. * 0.6);
+pub const PLANE_COLLIDER_OFFSET : Vec2 = Vec2::new(10., 0.);
+
+pub const CEILING_POSITION : Vec3 = Vec3::new(0., SCREEN_HEIGHT * 0.5 + 15., 0.);
+pub const GROUND_POSITION : Vec3 = Vec3::new(0., -(SCREEN_HEIGHT - 71.) / 2., z::GROUND);
+
+/// The rules of a level: starting it, travelling to the end and crashing.
+/// Nothing here draws anything, so it runs headless as well as in the game.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.init_resource::<Crash>()
+			// Nothing moves on after the tick the plane crashed on, however
+			// many more ticks the frame has left to run
+			.edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+				schedule.configure_set(PhysicsSet.run_if(|crash : Res<Crash>| crash.0.is_none()));
+			})
+			.add_system(start_level.in_schedule(OnEnter(AppState::Game)))
+			.add_system(
+				travel
+					.in_set(PhysicsSet)
+					.after(FlapInputSet)
+					.in_schedule(CoreSchedule::FixedUpdate)
+			)
+			.add_system(
+				collide
+					.after(PhysicsSet)
+					.run_if(in_state(GameState::Play))
+					.in_schedule(CoreSchedule::FixedUpdate)
+			)
+		;
+	}
+}
+
+// Resources
+// =========================================================================
+
+/// What the plane crashed into this level, if it has
+#[derive(Resource, Default)]
+pub struct Crash (pub Option<CollisionKind>);
+
+// Bundles
+// =========================================================================
+
+/// The plane's physics body, as a child of a root at `PLANE_START`
+pub fn plane_body () -> impl Bundle {
+	let transform = Transform::from_translation(PLANE_OFFSET);
+	
+	(
+		Velocity::default(),
+		AngularVelocity::default(),
+		PhysicsTransform::new(transform),
+		OBBCollider(PLANE_COLLIDER, Some(PLANE_COLLIDER_OFFSET)),
+		CollisionFilter { layers: layer::PLAYER, ..default() },
+	)
+}
+
+pub fn ceiling () -> impl Bundle {
+	(
+		TransformBundle::from_transform(Transform::from_translation(CEILING_POSITION)),
+		AABBCollider(Vec2::new(SCREEN_WIDTH, 30.), None),
+		CollisionKind::Ceiling,
+		CollisionFilter { layers: layer::WORLD, ..default() },
+	)
+}
+
+/// The ground's collider, for an entity at `GROUND_POSITION`
+pub fn ground_collider () -> impl Bundle {
+	(
+		AABBCollider(Vec2::new(SCREEN_WIDTH, 30.), None),
+		CollisionKind::Ground,
+		CollisionFilter { layers: layer::WORLD, ..default() },
+	)
+}
+
+// Helpers
+// =========================================================================
+
+/// How fast the ground scrolls past
+pub fn ground_speed (level : &Level) -> f32 {
+	300. + level.spawner.speed
+}
+
+// Systems
+// =========================================================================
+
+fn start_level (
+	mut commands : Commands,
+	level : Res<Level>,
+	config : Res<PhysicsConfig>,
+	catalogue : Res<ObstacleCatalogue>,
+	mut timer : ResMut<SpawnTimer>,
+	mut distance_travelled : ResMut<DistanceTravelled>,
+	mut rng : ResMut<GameRng>,
+	mut crash : ResMut<Crash>,
+) {
+	// Reset counters
+	distance_travelled.0 = 0.;
+	crash.0 = None;
+	
+	// Replaying a level always spawns the same obstacles
+	rng.start_level(level.index);
+	
+	timer.start(&level.spawner);
+	
+	// Obstacles are checked against where the plane could be as they spawn
+	commands.insert_resource(CourseCheck::new(&config, &catalogue, &level.spawner));
+}
+
+fn travel (
+	mut distance_travelled : ResMut<DistanceTravelled>,
+	fixed_time : Res<FixedTime>,
+	level : Res<Level>,
+	mut state : ResMut<NextState<GameState>>,
+) {
+	distance_travelled.0 += DIST_PER_SECOND * fixed_time.period.as_secs_f32();
+	
+	if distance_travelled.0 >= level.distance {
+		state.set(GameState::Exit);
+	}
+}
+
+fn collide (
+	mut reader : EventReader<CollisionEvent>,
+	level : Res<Level>,
+	mut state : ResMut<NextState<GameState>>,
+	mut velocity_query : Query<&mut Velocity>,
+	mut crash : ResMut<Crash>,
+) {
+	for event in reader.iter().filter(|event| !event.sensor) {
+		// The crashed plane gets dragged along by whatever it hit
+		let death_speed = match event.kind {
+			CollisionKind::Ground | CollisionKind::Ceiling => ground_speed(&level) * 0.8,
+			CollisionKind::Rock => level.spawner.speed,
+		};
+		
+		if let Ok(mut velocity) = velocity_query.get_mut(event.player) {
+			velocity.0.x = -death_speed;
+		}
+		
+		crash.0.get_or_insert(event.kind);
+		state.set(GameState::Dead);
+	}
+}
@@ -0,0 +1,146 @@
+pub mod actions;
+pub mod autopilot;
+pub mod assets;
+pub mod sprite_animation;
+pub mod storage;
+pub mod physics;
+pub mod replay;
+pub mod rng;
+#[cfg(feature = "debug")]
+pub mod debug;
+pub mod ghost;
+pub mod level;
+pub mod shaders;
+pub mod obstacle;
+pub mod scenes;
+pub mod transitions;
+
+use bevy::prelude::*;
+use rand::distributions::Standard;
+use rand::prelude::Distribution;
+use rand::Rng;
+use serde::Deserialize;
+use crate::obstacle::ObstacleSpawner;
+use crate::rng::{GameRng, Stream};
+
+// Constants
+// =========================================================================
+
+// Screen size
+// -------------------------------------------------------------------------
+
+pub const SCREEN_WIDTH  : f32 = 800.;
+pub const SCREEN_HEIGHT : f32 = 480.;
+
+// Z-indexes
+// -------------------------------------------------------------------------
+
+pub mod z {
+	pub const BACKGROUND : f32 = 0.;
+	pub const OBSTACLE   : f32 = 2.;
+	pub const GROUND     : f32 = 4.5;
+	pub const PLANE      : f32 = 5.;
+	pub const GAME_TEXT  : f32 = 6.;
+	pub const UI         : f32 = 50.;
+	pub const OVERLAY    : f32 = 60.;
+	pub const TRANSITION : f32 = 100.;
+}
+
+// Tween Events
+// -------------------------------------------------------------------------
+
+pub const GAME_IN_ANIM_COMPLETE     : u64 = 1;
+pub const GAME_OUT_ANIM_COMPLETE    : u64 = 2;
+pub const GAME_OVER_ANIM_COMPLETE   : u64 = 3;
+pub const TRANSITION_START_COMPLETE : u64 = 4;
+pub const TRANSITION_END_COMPLETE   : u64 = 5;
+
+// Misc
+// -------------------------------------------------------------------------
+
+pub const DIST_PER_SECOND : f32 = 30.;
+
+// States
+// =========================================================================
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, States)]
+pub enum AppState {
+	#[default]
+	Menu,
+	Game,
+	Settings,
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, States)]
+pub enum GameState {
+	#[default]
+	PreEnter,
+	Enter,
+	Play,
+	Exit,
+	Dead,
+	Paused,
+}
+
+// Structs
+// =========================================================================
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+pub enum LevelTheme {
+	Grass,
+	Snow,
+	Ice,
+}
+
+impl Distribution<LevelTheme> for Standard {
+	fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> LevelTheme {
+		match rng.gen_range(0..=2) {
+			0 => LevelTheme::Grass,
+			1 => LevelTheme::Snow,
+			_ => LevelTheme::Ice,
+		}
+	}
+}
+
+#[derive(Resource, Clone)]
+pub struct Level {
+	pub index    : usize,
+	pub theme    : LevelTheme,
+	pub distance : f32, // Distance the player needs to travel to "complete" the level
+	pub spawner  : ObstacleSpawner,
+}
+
+impl Level {
+	/// The level after this one, a little longer and harder
+	pub fn next (&self, rng : &GameRng) -> Level {
+		let index = self.index + 1;
+		
+		Level {
+			index,
+			theme: rng.level(index, Stream::Themes).gen(),
+			distance: self.distance + 100.,
+			spawner: ObstacleSpawner {
+				speed: self.spawner.speed + 5.,
+				interval: self.spawner.interval - 0.1,
+				margin: (self.spawner.margin - 5.).max(0.),
+				..self.spawner
+			},
+		}
+	}
+}
+
+pub const BASE_LEVEL : Level = Level {
+	index: 1,
+	theme: LevelTheme::Grass,
+	distance: 1000.,
+	spawner: ObstacleSpawner {
+		speed: 150.,
+		interval: 2.,
+		gap_min: 150.,
+		gap_max: 200.,
+		margin: 60.,
+	},
+};
+
+#[derive(Resource)]
+pub struct DistanceTravelled (pub f32);
@@ -1,126 +1,22 @@
-mod assets;
-mod sprite_animation;
-mod physics;
-#[cfg(feature = "debug")]
-mod debug;
-mod shaders;
-mod obstacle;
-mod scenes;
-mod transitions;
-
 use bevy::prelude::*;
 use bevy_tweening::TweeningPlugin;
-use rand::distributions::Standard;
-use rand::prelude::Distribution;
-use rand::Rng;
-use crate::assets::AssetsPlugin;
+use tappy_plane::{AppState, BASE_LEVEL, DistanceTravelled, GameState, SCREEN_HEIGHT, SCREEN_WIDTH};
+use tappy_plane::actions::ActionsPlugin;
+use tappy_plane::assets::AssetsPlugin;
+use tappy_plane::autopilot::AutopilotPlugin;
 #[cfg(feature = "debug")]
-use crate::debug::DebugPlugin;
-use crate::obstacle::{ObstaclePlugin, ObstacleSpawner};
-use crate::physics::PhysicsPlugin;
-use crate::scenes::ScenesPlugin;
-use crate::shaders::ShadersPlugin;
-use crate::sprite_animation::SpriteAnimationPlugin;
-use crate::transitions::TransitionsPlugin;
-
-// Constants
-// =========================================================================
-
-// Screen size
-// -------------------------------------------------------------------------
-
-const SCREEN_WIDTH  : f32 = 800.;
-const SCREEN_HEIGHT : f32 = 480.;
-
-// Z-indexes
-// -------------------------------------------------------------------------
-
-mod z {
-	pub const BACKGROUND : f32 = 0.;
-	pub const OBSTACLE   : f32 = 2.;
-	pub const GROUND     : f32 = 4.5;
-	pub const PLANE      : f32 = 5.;
-	pub const GAME_TEXT  : f32 = 6.;
-	pub const UI         : f32 = 50.;
-	pub const TRANSITION : f32 = 100.;
-}
-
-// Tween Events
-// -------------------------------------------------------------------------
-
-const GAME_IN_ANIM_COMPLETE     : u64 = 1;
-const GAME_OUT_ANIM_COMPLETE    : u64 = 2;
-const GAME_OVER_ANIM_COMPLETE   : u64 = 3;
-const TRANSITION_START_COMPLETE : u64 = 4;
-const TRANSITION_END_COMPLETE   : u64 = 5;
-
-// Misc
-// -------------------------------------------------------------------------
-
-const DIST_PER_SECOND : f32 = 30.;
-
-// States
-// =========================================================================
-
-#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, States)]
-pub enum AppState {
-	#[default]
-	Menu,
-	Game,
-}
-
-#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, States)]
-pub enum GameState {
-	#[default]
-	PreEnter,
-	Enter,
-	Play,
-	Exit,
-	Dead,
-}
-
-// Structs
-// =========================================================================
-
-#[derive(Copy, Clone)]
-pub enum LevelTheme {
-	Grass,
-	Snow,
-	Ice,
-}
-
-impl Distribution<LevelTheme> for Standard {
-	fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> LevelTheme {
-		match rng.gen_range(0..=2) {
-			0 => LevelTheme::Grass,
-			1 => LevelTheme::Snow,
-			_ => LevelTheme::Ice,
-		}
-	}
-}
-
-#[derive(Resource)]
-pub struct Level {
-	pub index    : usize,
-	pub theme    : LevelTheme,
-	pub distance : f32, // Distance the player needs to travel to "complete" the level
-	pub spawner  : ObstacleSpawner,
-}
-
-pub const BASE_LEVEL : Level = Level {
-	index: 1,
-	theme: LevelTheme::Grass,
-	distance: 1000.,
-	spawner: ObstacleSpawner {
-		speed: 150.,
-		interval: 2.,
-		gap_min: 150.,
-		gap_max: 200.,
-	},
-};
-
-#[derive(Resource)]
-pub struct DistanceTravelled (pub f32);
+use tappy_plane::debug::DebugPlugin;
+use tappy_plane::ghost::GhostPlugin;
+use tappy_plane::level::LevelPlugin;
+use tappy_plane::obstacle::ObstaclePlugin;
+use tappy_plane::physics::PhysicsPlugin;
+use tappy_plane::replay::ReplayPlugin;
+use tappy_plane::rng::RngPlugin;
+use tappy_plane::scenes::ScenesPlugin;
+use tappy_plane::shaders::ShadersPlugin;
+use tappy_plane::sprite_animation::SpriteAnimationPlugin;
+use tappy_plane::storage::StoragePlugin;
+use tappy_plane::transitions::TransitionsPlugin;
 
 // Game
 // =========================================================================
@@ -142,7 +38,14 @@ fn main() {
 				..default()
 			}),
 			..default()
+		}).set(AssetPlugin {
+			// Hot-reload assets (like physics tuning) while debugging
+			watch_for_changes: cfg!(feature = "debug"),
+			..default()
 		}))
+		.add_plugin(StoragePlugin)
+		.add_plugin(RngPlugin)
+		.add_plugin(ActionsPlugin)
 		.add_plugin(TweeningPlugin)
 		.add_plugin(ShadersPlugin)
 		.add_plugin(AssetsPlugin)
@@ -150,6 +53,10 @@ fn main() {
 		.add_plugin(SpriteAnimationPlugin)
 		.add_plugin(PhysicsPlugin)
 		.add_plugin(ObstaclePlugin)
+		.add_plugin(LevelPlugin)
+		.add_plugin(AutopilotPlugin)
+		.add_plugin(ReplayPlugin)
+		.add_plugin(GhostPlugin)
 		.add_plugin(ScenesPlugin)
 		.add_system(setup.on_startup())
 	;
@@ -0,0 +1,357 @@
+use bevy::asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use crate::assets::SPRITES;
+use crate::obstacle::motion::{Curve, Motion, Movement};
+use crate::{BASE_LEVEL, LevelTheme};
+
+// Resources
+// =========================================================================
+
+/// Every rock an obstacle can be built from, the patterns they're put
+/// together in, and the ways they can move. Loaded from
+/// `rocks.obstacles.ron` and kept in sync with it, like the physics tuning.
+#[derive(Resource, Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "0f6c1f52-3c1e-4d8b-9a77-5b0f2e1d6a43"]
+pub struct ObstacleCatalogue {
+	pub rocks    : Vec<RockDef>,
+	pub patterns : Vec<PatternDef>,
+	pub motions  : Vec<MotionDef>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RockDef {
+	pub sprite      : String,
+	/// The rock's shape, relative to the sprite's centre
+	pub collider    : Vec<Vec2>,
+	/// Collide with the hulls traced from the sprite instead, using
+	/// `collider` only until they've been traced
+	#[serde(default)]
+	pub trace       : bool,
+	pub orientation : Orientation,
+	/// How often the rock is picked, relative to the others that fit
+	pub weight      : f32,
+	pub themes      : Vec<LevelTheme>,
+}
+
+/// A named arrangement of columns, spawned as one obstacle
+#[derive(Deserialize, Clone, Debug)]
+pub struct PatternDef {
+	pub name       : String,
+	/// The first level the pattern can appear on
+	pub difficulty : usize,
+	/// How often the pattern is picked, relative to the others available
+	pub weight     : f32,
+	/// Left to right
+	pub columns    : Vec<ColumnDef>,
+}
+
+/// A gap in a pattern, with rocks above and below it
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ColumnDef {
+	/// Across from the pattern's first column
+	pub x      : f32,
+	/// How far the gap's centre is above the pattern's
+	pub rise   : f32,
+	/// The gap's size, as a fraction of the one drawn for the pattern
+	pub size   : f32,
+	/// Whether there's a rock above the gap
+	pub top    : bool,
+	/// Whether there's a rock below the gap
+	pub bottom : bool,
+}
+
+impl Default for ColumnDef {
+	fn default () -> Self {
+		ColumnDef {
+			x: 0.,
+			rise: 0.,
+			size: 1.,
+			top: true,
+			bottom: true,
+		}
+	}
+}
+
+/// A way an obstacle's rocks can move, picked for each obstacle spawned
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MotionDef {
+	pub name       : String,
+	/// The first level the motion can appear on
+	pub difficulty : usize,
+	/// How often the motion is picked, relative to the others available
+	pub weight     : f32,
+	pub themes     : Vec<LevelTheme>,
+	pub movement   : Movement,
+	pub curve      : Curve,
+	/// Furthest a rock moves from where it was put, in pixels
+	pub amplitude  : f32,
+	/// Seconds to move there and back
+	pub period     : f32,
+}
+
+impl Default for MotionDef {
+	fn default () -> Self {
+		MotionDef {
+			name: String::new(),
+			difficulty: 1,
+			weight: 1.,
+			themes: vec![LevelTheme::Grass, LevelTheme::Snow, LevelTheme::Ice],
+			movement: Movement::Still,
+			curve: Curve::Sine,
+			amplitude: 0.,
+			period: 1.,
+		}
+	}
+}
+
+impl MotionDef {
+	/// How rocks picked with this move, or `None` if they stay put
+	pub fn motion (&self) -> Option<Motion> {
+		(self.movement != Movement::Still).then_some(Motion {
+			movement: self.movement,
+			curve: self.curve,
+			amplitude: self.amplitude,
+			period: self.period,
+		})
+	}
+}
+
+/// Which side of the gap a rock is on
+#[derive(Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Orientation {
+	/// Stands on the bottom of the screen, pointing up
+	Up,
+	/// Hangs from the top, pointing down
+	Down,
+}
+
+impl Default for ObstacleCatalogue {
+	/// The catalogue the game ships with, for before the asset has loaded
+	fn default () -> Self {
+		ObstacleCatalogue::from_bytes(include_bytes!("../../assets/rocks.obstacles.ron"))
+			.expect("rocks.obstacles.ron is valid")
+	}
+}
+
+impl ObstacleCatalogue {
+	/// Reads a catalogue, making sure every theme has rocks for both sides
+	/// of a gap, that each rock's sprite is on the sheet and it has a shape
+	/// to collide with, that the first level has patterns to pick from, that
+	/// moving rocks can get round, and that no weight is negative or infinite
+	pub fn from_bytes (bytes : &[u8]) -> Result<Self, Error> {
+		let catalogue = ron::de::from_bytes::<ObstacleCatalogue>(bytes)?;
+		
+		let weights = catalogue.rocks.iter().map(|rock| (&rock.sprite, rock.weight))
+			.chain(catalogue.patterns.iter().map(|pattern| (&pattern.name, pattern.weight)))
+			.chain(catalogue.motions.iter().map(|motion| (&motion.name, motion.weight)));
+		
+		for (name, weight) in weights {
+			if !weight.is_finite() || weight < 0. {
+				return Err(Error::msg(format!("{} has to have a weight of 0 or more", name)));
+			}
+		}
+		
+		for rock in &catalogue.rocks {
+			if sprite_size(&rock.sprite).is_none() {
+				return Err(Error::msg(format!("{} isn't on the sprite sheet", rock.sprite)));
+			}
+			
+			// Fewer points don't make a shape to collide with
+			if rock.collider.len() < 3 {
+				return Err(Error::msg(format!("{} needs at least 3 collider points", rock.sprite)));
+			}
+		}
+		
+		for pattern in &catalogue.patterns {
+			let columns = &pattern.columns;
+			
+			if columns.is_empty() || columns[0].x != 0. {
+				return Err(Error::msg(format!("{} has to start with a column at 0", pattern.name)));
+			}
+			
+			if columns.windows(2).any(|pair| pair[1].x < pair[0].x) {
+				return Err(Error::msg(format!("{}'s columns have to go left to right", pattern.name)));
+			}
+			
+			if columns.iter().any(|column| !column.top && !column.bottom) {
+				return Err(Error::msg(format!("{} has a column without rocks", pattern.name)));
+			}
+		}
+		
+		for motion in &catalogue.motions {
+			if motion.movement != Movement::Still && motion.period <= 0. {
+				return Err(Error::msg(format!("{} has to take some time to move", motion.name)));
+			}
+		}
+		
+		if catalogue.available(BASE_LEVEL.index).map(|pattern| pattern.weight).sum::<f32>() <= 0. {
+			return Err(Error::msg(format!("no patterns for level {}", BASE_LEVEL.index)));
+		}
+		
+		for theme in [LevelTheme::Grass, LevelTheme::Snow, LevelTheme::Ice] {
+			for orientation in [Orientation::Up, Orientation::Down] {
+				let weight : f32 = catalogue.fitting(theme, orientation)
+					.map(|rock| rock.weight)
+					.sum();
+				
+				if weight <= 0. {
+					return Err(Error::msg(format!("no {:?} rocks for {:?} levels", orientation, theme)));
+				}
+			}
+		}
+		
+		Ok(catalogue)
+	}
+	
+	/// Half the width of the widest rock
+	pub fn rock_half_width (&self) -> f32 {
+		self.rocks.iter()
+			.filter_map(|rock| sprite_size(&rock.sprite))
+			.fold(0., |widest, size| size.x.max(widest)) * 0.5
+	}
+	
+	/// Picks a rock for one side of a gap at random, by weight
+	pub fn pick (&self, rng : &mut impl Rng, theme : LevelTheme, orientation : Orientation) -> &RockDef {
+		let fitting : Vec<_> = self.fitting(theme, orientation).collect();
+		
+		*fitting.choose_weighted(rng, |rock| rock.weight)
+			.expect("catalogue was checked for rocks on load")
+	}
+	
+	/// Picks a pattern available on level `index` at random, by weight
+	pub fn pick_pattern (&self, rng : &mut impl Rng, index : usize) -> &PatternDef {
+		let available : Vec<_> = self.available(index).collect();
+		
+		*available.choose_weighted(rng, |pattern| pattern.weight)
+			.expect("catalogue was checked for patterns on load")
+	}
+	
+	/// Picks how an obstacle on level `index` moves at random, by weight,
+	/// from the motions for `theme`. Obstacles stay still if there are none.
+	pub fn pick_motion (&self, rng : &mut impl Rng, theme : LevelTheme, index : usize) -> Option<Motion> {
+		let available : Vec<_> = self.motions.iter()
+			.filter(|motion| motion.difficulty <= index && motion.themes.contains(&theme))
+			.collect();
+		
+		available.choose_weighted(rng, |motion| motion.weight)
+			.ok()
+			.and_then(|motion| motion.motion())
+	}
+	
+	fn available (&self, index : usize) -> impl Iterator<Item = &PatternDef> {
+		self.patterns.iter().filter(move |pattern| pattern.difficulty <= index)
+	}
+	
+	fn fitting (&self, theme : LevelTheme, orientation : Orientation) -> impl Iterator<Item = &RockDef> {
+		self.rocks.iter().filter(move |rock| {
+			rock.orientation == orientation && rock.themes.contains(&theme)
+		})
+	}
+}
+
+impl RockDef {
+	/// Half the height of the rock's sprite, so its tip can be put at the
+	/// edge of a gap
+	pub fn half_height (&self) -> f32 {
+		sprite_size(&self.sprite).unwrap_or_default().y * 0.5
+	}
+}
+
+/// The catalogue asset currently in use
+#[derive(Resource, Default)]
+pub struct ObstacleCatalogueHandle (pub Handle<ObstacleCatalogue>);
+
+// Loader
+// =========================================================================
+
+#[derive(Default)]
+pub struct ObstacleCatalogueLoader;
+
+impl AssetLoader for ObstacleCatalogueLoader {
+	fn load<'a> (
+		&'a self,
+		bytes : &'a [u8],
+		load_context : &'a mut LoadContext,
+	) -> BoxedFuture<'a, Result<(), Error>> {
+		Box::pin(async move {
+			let catalogue = ObstacleCatalogue::from_bytes(bytes)?;
+			load_context.set_default_asset(LoadedAsset::new(catalogue));
+			Ok(())
+		})
+	}
+	
+	fn extensions (&self) -> &[&str] {
+		&["obstacles.ron"]
+	}
+}
+
+// Systems
+// =========================================================================
+
+pub fn load_catalogue (
+	asset_server : Res<AssetServer>,
+	mut handle : ResMut<ObstacleCatalogueHandle>,
+) {
+	handle.0 = asset_server.load("rocks.obstacles.ron");
+}
+
+pub fn apply_catalogue (
+	mut reader : EventReader<AssetEvent<ObstacleCatalogue>>,
+	assets : Res<Assets<ObstacleCatalogue>>,
+	handle : Res<ObstacleCatalogueHandle>,
+	mut catalogue : ResMut<ObstacleCatalogue>,
+) {
+	for event in reader.iter() {
+		match event {
+			AssetEvent::Created { handle: h } | AssetEvent::Modified { handle: h } => {
+				if *h != handle.0 { continue; }
+				
+				if let Some(loaded) = assets.get(h) {
+					*catalogue = loaded.clone();
+				}
+			}
+			AssetEvent::Removed { .. } => {}
+		}
+	}
+}
+
+// Helpers
+// =========================================================================
+
+fn sprite_size (name : &str) -> Option<Vec2> {
+	SPRITES.iter()
+		.find(|&&(sprite, ..)| sprite == name)
+		.map(|&(.., width, height)| Vec2::new(width, height))
+}
+
+// Tests
+// =========================================================================
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn shipped_catalogue_loads () {
+		let catalogue = ObstacleCatalogue::default();
+		
+		assert!(!catalogue.rocks.is_empty());
+		assert!(catalogue.rocks.iter().all(|rock| rock.collider.len() >= 3));
+	}
+	
+	#[test]
+	fn rejects_bad_weights () {
+		let text = std::str::from_utf8(include_bytes!("../../assets/rocks.obstacles.ron")).unwrap();
+		
+		for weight in ["-1.0", "inf", "NaN"] {
+			let bad = text.replacen("weight: 1.0", &format!("weight: {}", weight), 1);
+			assert!(ObstacleCatalogue::from_bytes(bad.as_bytes()).is_err(), "weight {}", weight);
+		}
+	}
+}
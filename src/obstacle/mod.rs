@@ -0,0 +1,503 @@
+mod catalogue;
+pub mod motion;
+pub mod reach;
+
+use std::time::Duration;
+use bevy::prelude::*;
+use rand::Rng;
+use crate::assets::{SpriteHulls, SpriteSheet};
+use crate::obstacle::catalogue::*;
+use crate::obstacle::motion::{Motion, move_rocks, Movement, RockMotion};
+use crate::obstacle::reach::{CourseCheck, Gap};
+use crate::rng::GameRng;
+use crate::physics::{CollisionFilter, CollisionKind, FlapInputSet, layer, PhysicsSet, PhysicsTick, SATCollider, TIMESTEP};
+use crate::{AppState, DIST_PER_SECOND, DistanceTravelled, GameState, Level, LevelTheme, SCREEN_WIDTH, z};
+use crate::scenes::GameRoot;
+
+pub use catalogue::{ColumnDef, MotionDef, ObstacleCatalogue, ObstacleCatalogueHandle, Orientation, PatternDef, RockDef};
+
+const SPAWN_OFFSET     : f32 = SCREEN_WIDTH * 0.5 + 100.;
+const NEG_SPAWN_OFFSET : f32 = SCREEN_WIDTH * -0.5 - 200.;
+
+/// Furthest a rock is nudged sideways from its column
+const ROCK_JITTER      : f32 = 10.;
+
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.insert_resource(
+				SpawnTimer(Timer::new(Duration::default(), TimerMode::Repeating))
+			)
+			.init_resource::<ObstacleCatalogue>()
+			.init_resource::<ObstacleCatalogueHandle>()
+			.add_asset::<ObstacleCatalogue>()
+			.init_asset_loader::<ObstacleCatalogueLoader>()
+			.add_startup_system(load_catalogue)
+			.add_system(apply_catalogue)
+			.add_system(despawn_obstacle.in_set(OnUpdate(AppState::Game)))
+			// Obstacles move on the fixed step, ahead of physics, so a run
+			// plays out the same whatever the frame rate
+			.add_system(
+				move_obstacle
+					.run_if(in_state(AppState::Game))
+					.run_if(not(in_state(GameState::Paused)))
+					.before(PhysicsSet)
+					.in_schedule(CoreSchedule::FixedUpdate)
+			)
+			.add_system(
+				move_rocks
+					.run_if(in_state(AppState::Game))
+					.run_if(not(in_state(GameState::Paused)))
+					.after(move_obstacle)
+					.before(PhysicsSet)
+					.in_schedule(CoreSchedule::FixedUpdate)
+			)
+			.add_system(
+				spawn_obstacle
+					.in_set(PhysicsSet)
+					.before(FlapInputSet)
+					.in_schedule(CoreSchedule::FixedUpdate)
+			)
+		;
+	}
+}
+
+// Resources
+// =========================================================================
+
+#[derive(Resource)]
+pub struct SpawnTimer (pub Timer);
+
+impl SpawnTimer {
+	/// Sets the timer up for the start of a level, already finished so the
+	/// first obstacle spawns right away
+	pub fn start (&mut self, spawner : &ObstacleSpawner) {
+		let interval = Duration::from_secs_f32(spawner.interval);
+		self.0.set_duration(interval);
+		self.0.set_elapsed(interval);
+	}
+	
+	/// Waits the spawner's interval from the end of an obstacle `width`
+	/// across, rather than its start
+	pub fn wait (&mut self, spawner : &ObstacleSpawner, width : f32) {
+		self.0.set_duration(Duration::from_secs_f32(spawner.interval + width / spawner.speed));
+	}
+}
+
+#[derive(Clone)]
+pub struct ObstacleSpawner {
+	pub speed    : f32,
+	pub interval : f32,
+	pub gap_min  : f32,
+	pub gap_max  : f32,
+	/// How far inside the heights the plane can reach each gap is centred.
+	/// Smaller is harder, with the plane needing longer to line up.
+	pub margin   : f32,
+}
+
+// Components
+// =========================================================================
+
+#[derive(Component)]
+pub struct Obstacle {
+	/// From the first column to the last
+	pub width : f32,
+}
+
+// Systems
+// =========================================================================
+
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_obstacle (
+	mut commands : Commands,
+	sprite_sheet : Res<SpriteSheet>,
+	hulls : Res<SpriteHulls>,
+	root_query : Query<Entity, With<GameRoot>>,
+	fixed_time : Res<FixedTime>,
+	distance_travelled : Res<DistanceTravelled>,
+	tick : Res<PhysicsTick>,
+	level : Res<Level>,
+	catalogue : Res<ObstacleCatalogue>,
+	mut timer : ResMut<SpawnTimer>,
+	mut rng : ResMut<GameRng>,
+	mut check : ResMut<CourseCheck>,
+) {
+	let distance_before_end = level.distance - DIST_PER_SECOND;
+	let root = root_query.single();
+	timer.0.tick(fixed_time.period);
+	
+	if timer.0.just_finished() && distance_travelled.0 < distance_before_end {
+		let pattern = catalogue.pick_pattern(rng.obstacles(), level.index);
+		let motion = catalogue.pick_motion(rng.obstacles(), level.theme, level.index);
+		let placement = place_pattern(rng.obstacles(), pattern, motion, &level.spawner, &mut check, tick.0);
+		
+		if !placement.passable {
+			warn!(
+				"Level {} has a {} on tick {} the plane can't get through",
+				level.index, pattern.name, tick.0,
+			);
+		}
+		
+		let layout = layout(rng.obstacles(), &catalogue, level.theme, placement);
+		
+		timer.wait(&level.spawner, layout.width());
+		
+		commands.entity(root).with_children(|commands| {
+			spawn(commands, &sprite_sheet, &hulls, SPAWN_OFFSET, &layout);
+		});
+	}
+}
+
+pub fn move_obstacle (
+	mut query : Query<&mut Transform, With<Obstacle>>,
+	fixed_time : Res<FixedTime>,
+	level : Res<Level>,
+) {
+	let spawner = &level.spawner;
+	
+	for mut transform in &mut query {
+		transform.translation.x -= spawner.speed * fixed_time.period.as_secs_f32();
+	}
+}
+
+pub fn despawn_obstacle (
+	mut commands : Commands,
+	query : Query<(Entity, &Transform, &Obstacle)>,
+) {
+	for (entity, transform, obstacle) in &query {
+		if transform.translation.x + obstacle.width < NEG_SPAWN_OFFSET {
+			commands.entity(entity).despawn_recursive();
+		}
+	}
+}
+
+// Layout
+// =========================================================================
+
+/// Where an obstacle's gaps and rocks go, worked out before anything is
+/// spawned so it can be checked without a world
+pub struct Layout {
+	pub columns : Vec<Column>,
+	pub motion  : Option<Motion>,
+	pub rocks   : Vec<Rock>,
+}
+
+impl Layout {
+	pub fn width (&self) -> f32 {
+		self.columns.last().map_or(0., |column| column.x)
+	}
+}
+
+/// A gap placed for a pattern's column
+#[derive(Copy, Clone, Debug)]
+pub struct Column {
+	/// Across from the obstacle
+	pub x      : f32,
+	pub gap    : Gap,
+	pub top    : bool,
+	pub bottom : bool,
+}
+
+pub struct Rock {
+	pub sprite      : String,
+	pub orientation : Orientation,
+	/// Relative to the obstacle, before it moves
+	pub position    : Vec3,
+	pub collider    : Vec<Vec2>,
+	/// Whether to collide with the sprite's traced hulls over `collider`
+	pub trace       : bool,
+}
+
+/// Where `place_pattern` put a pattern's gaps
+pub struct Placement {
+	pub columns  : Vec<Column>,
+	pub motion   : Option<Motion>,
+	/// Whether the plane can fly all the way through
+	pub passable : bool,
+}
+
+/// Places `pattern` with gaps between `spawner.gap_min` and `spawner.gap_max`,
+/// centred so the plane can get to its first column from the obstacles
+/// before it, and moving with `motion`
+pub fn place_pattern (
+	rng : &mut impl Rng,
+	pattern : &PatternDef,
+	motion : Option<Motion>,
+	spawner : &ObstacleSpawner,
+	check : &mut CourseCheck,
+	spawned : u32,
+) -> Placement {
+	let size = rng.gen_range(spawner.gap_min ..= spawner.gap_max);
+	
+	// A gap that breathes never closes past half its size
+	let motion = motion.map(|motion| match motion.movement {
+		Movement::Breathe => {
+			let smallest = pattern.columns.iter()
+				.filter(|column| column.top && column.bottom)
+				.map(|column| size * column.size)
+				.fold(f32::MAX, f32::min);
+			
+			Motion { amplitude: motion.amplitude.min(smallest * 0.25), ..motion }
+		}
+		_ => motion,
+	});
+	
+	let sway = motion.map_or(0., |motion| motion.amplitude);
+	
+	// Every rock has to reach the edge of the screen, wherever it moves to
+	let (min, max) = pattern.columns.iter().fold(
+		(size * -0.5, size * 0.5),
+		|(min, max), column| {
+			let half = size * column.size * 0.5;
+			
+			(
+				if column.top { min.max(-half - column.rise + sway) } else { min },
+				if column.bottom { max.min(half - column.rise - sway) } else { max },
+			)
+		},
+	);
+	
+	let (min, max) = if min <= max { (min, max) } else { ((min + max) * 0.5, (min + max) * 0.5) };
+	
+	// Where the pattern's centre is when the plane is lined up with its first
+	// column, which always starts at 0
+	let first = &pattern.columns[0];
+	
+	let (reach, (low, high)) = match check.reach(spawned, 0.) {
+		Some((lo, hi)) => {
+			let (lo, hi) = (lo - first.rise, hi - first.rise);
+			let middle = (lo + hi) * 0.5;
+			let margin = spawner.margin.min((hi - lo) * 0.5);
+			
+			(
+				middle.clamp(min, max),
+				((lo + margin).clamp(min, max), (hi - margin).clamp(min, max)),
+			)
+		}
+		None => (0_f32.clamp(min, max), (min, max)),
+	};
+	
+	let place = |centre : f32| -> Vec<Column> {
+		pattern.columns.iter().map(|column| {
+			let centre = centre + column.rise;
+			let half = size * column.size * 0.5;
+			
+			Column {
+				x: column.x,
+				gap: Gap {
+					top: if column.top { centre + half } else { f32::MAX },
+					bottom: if column.bottom { centre - half } else { f32::MIN },
+				},
+				top: column.top,
+				bottom: column.bottom,
+			}
+		}).collect()
+	};
+	
+	// Somewhere in reach, then as near the middle of it as the screen allows
+	// if flying the whole pattern turns out not to be
+	let candidates = [rng.gen_range(low ..= high), reach].map(place);
+	
+	for columns in &candidates {
+		let gaps : Vec<_> = columns.iter().map(|column| (column.x, column.gap)).collect();
+		
+		if check.add(spawned, &gaps, motion) {
+			return Placement { columns: columns.clone(), motion, passable: true };
+		}
+	}
+	
+	let [columns, _] = candidates;
+	Placement { columns, motion, passable: false }
+}
+
+/// Places rocks from the catalogue above and below each column's gap, with
+/// a few decorative ones either side
+pub fn layout (
+	rng : &mut impl Rng,
+	catalogue : &ObstacleCatalogue,
+	theme : LevelTheme,
+	placement : Placement,
+) -> Layout {
+	let Placement { columns, motion, .. } = placement;
+	let mut rocks = Vec::new();
+	
+	for orientation in [Orientation::Down, Orientation::Up] {
+		let sign = if orientation == Orientation::Down { 1. } else { -1. };
+		
+		let mut rock = |x : f32, y : f32, z : f32, edge : f32, def : &RockDef| {
+			rocks.push(Rock {
+				sprite: def.sprite.clone(),
+				orientation,
+				position: Vec3::new(x, edge + (def.half_height() + y) * sign, z),
+				collider: def.collider.clone(),
+				trace: def.trace,
+			});
+		};
+		
+		// Where each rock on this side of the gaps has its tip
+		let edges : Vec<_> = columns.iter().filter_map(|column| match orientation {
+			Orientation::Down => column.top.then_some((column.x, column.gap.top)),
+			Orientation::Up => column.bottom.then_some((column.x, column.gap.bottom)),
+		}).collect();
+		
+		for &(x, edge) in &edges {
+			rock(
+				x + rng.gen_range(-ROCK_JITTER..=ROCK_JITTER),
+				0.,
+				0.,
+				edge,
+				catalogue.pick(rng, theme, orientation),
+			);
+		}
+		
+		// Child before
+		if let Some(&(x, edge)) = edges.first() {
+			if rng.gen_bool(0.45) {
+				rock(
+					x + rng.gen_range(-80.0..=-30.),
+					rng.gen_range(50.0 ..= 100.),
+					0.1,
+					edge,
+					catalogue.pick(rng, theme, orientation),
+				);
+			}
+		}
+		
+		// Child after
+		if let Some(&(x, edge)) = edges.last() {
+			if rng.gen_bool(0.45) {
+				rock(
+					x + rng.gen_range(30.0..=80.),
+					rng.gen_range(50.0 ..= 100.),
+					0.2,
+					edge,
+					catalogue.pick(rng, theme, orientation),
+				);
+			}
+		}
+	}
+	
+	Layout { columns, motion, rocks }
+}
+
+/// Works out the physics ticks obstacles spawn on without running the game,
+/// ticking a `SpawnTimer` as `spawn_obstacle` does
+pub struct SpawnSchedule {
+	timer    : SpawnTimer,
+	period   : Duration,
+	tick     : u32,
+	distance : f32,
+	end      : f32,
+}
+
+impl SpawnSchedule {
+	pub fn new (level : &Level) -> Self {
+		let mut timer = SpawnTimer(Timer::new(Duration::default(), TimerMode::Repeating));
+		timer.start(&level.spawner);
+		
+		SpawnSchedule {
+			timer,
+			period: Duration::from_secs_f32(TIMESTEP),
+			tick: 0,
+			distance: 0.,
+			end: level.distance - DIST_PER_SECOND,
+		}
+	}
+	
+	/// Waits for an obstacle `width` across before the next, as
+	/// `spawn_obstacle` does
+	pub fn wait (&mut self, spawner : &ObstacleSpawner, width : f32) {
+		self.timer.wait(spawner, width);
+	}
+}
+
+impl Iterator for SpawnSchedule {
+	type Item = u32;
+	
+	/// The tick the next obstacle spawns on, or `None` once the level is too
+	/// close to its end for more
+	fn next (&mut self) -> Option<u32> {
+		while self.distance < self.end {
+			let tick = self.tick;
+			
+			self.timer.0.tick(self.period);
+			self.tick += 1;
+			self.distance += DIST_PER_SECOND * self.period.as_secs_f32();
+			
+			if self.timer.0.just_finished() {
+				return Some(tick);
+			}
+		}
+		
+		None
+	}
+}
+
+// Helpers
+// =========================================================================
+
+fn spawn (
+	commands : &mut ChildBuilder,
+	sprite_sheet : &SpriteSheet,
+	hulls : &SpriteHulls,
+	start_x : f32,
+	layout : &Layout,
+) {
+	commands.spawn((
+		Transform::from_xyz(start_x, 0., z::OBSTACLE),
+		GlobalTransform::default(),
+		Visibility::default(),
+		ComputedVisibility::default(),
+		Obstacle { width: layout.width() },
+	)).with_children(|commands| {
+		for rock in &layout.rocks {
+			let mut entity = commands.spawn((
+				SpriteSheetBundle {
+					texture_atlas: sprite_sheet.handle.clone(),
+					sprite: sprite_sheet.get(&rock.sprite),
+					transform: Transform::from_translation(rock.position + Vec3::Z * z::OBSTACLE),
+					..default()
+				},
+			));
+			
+			// The rock's colliders are its children, so they move with it
+			if let Some(motion) = layout.motion {
+				entity.insert(RockMotion {
+					motion,
+					orientation: rock.orientation,
+					base: rock.position.y,
+					ticks: 0,
+				});
+			}
+			
+			entity.with_children(|commands| {
+				let traced = hulls.get(&rock.sprite).filter(|_| rock.trace);
+				spawn_colliders(commands, traced, rock.collider.clone());
+			});
+		}
+	});
+}
+
+/// A collider for each hull traced from a rock's sprite, or the `fallback`
+/// polygon if there are none to use
+fn spawn_colliders (
+	commands : &mut ChildBuilder,
+	hulls : Option<&Vec<Vec<Vec2>>>,
+	fallback : Vec<Vec2>,
+) {
+	let hulls = hulls
+		.filter(|hulls| !hulls.is_empty())
+		.cloned()
+		.unwrap_or_else(|| vec![fallback]);
+	
+	for hull in hulls {
+		commands.spawn((
+			TransformBundle::default(),
+			SATCollider(hull),
+			CollisionKind::Rock,
+			CollisionFilter { layers: layer::OBSTACLE, ..default() },
+		));
+	}
+}
@@ -0,0 +1,137 @@
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::obstacle::Orientation;
+use crate::obstacle::reach::Gap;
+use crate::physics::TIMESTEP;
+
+// Structs
+// =========================================================================
+
+/// What an obstacle's rocks do as it scrolls past
+#[derive(Deserialize, Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Movement {
+	/// The rocks stay where they're put
+	#[default]
+	Still,
+	/// Every rock moves up and down together, taking the gaps with them
+	Shift,
+	/// The rocks above and below each gap move apart and back together,
+	/// opening and closing it
+	Breathe,
+}
+
+/// How a moving rock gets from one end of its travel to the other
+#[derive(Deserialize, Copy, Clone, Debug, Default)]
+pub enum Curve {
+	#[default]
+	Sine,
+	/// At a constant speed, turning straight round at either end
+	PingPong,
+	/// Eased from one end to the other and back
+	Tween(Ease),
+}
+
+/// Easing for `Curve::Tween`, named like `bevy_tweening`'s
+#[derive(Deserialize, Copy, Clone, Debug)]
+pub enum Ease {
+	QuadraticInOut,
+	CubicInOut,
+	QuarticInOut,
+}
+
+impl Ease {
+	fn sample (self, x : f32) -> f32 {
+		let power = match self {
+			Ease::QuadraticInOut => 2,
+			Ease::CubicInOut => 3,
+			Ease::QuarticInOut => 4,
+		};
+		
+		if x < 0.5 {
+			2_f32.powi(power - 1) * x.powi(power)
+		} else {
+			1. - (2. - 2. * x).powi(power) * 0.5
+		}
+	}
+}
+
+impl Curve {
+	/// Where along its travel a rock is at time `t`, in periods since it
+	/// spawned. Every curve starts in the middle, heading up.
+	fn wave (self, t : f32) -> f32 {
+		match self {
+			Curve::Sine => (t * TAU).sin(),
+			Curve::PingPong => 1. - 4. * ((t + 0.25).fract() - 0.5).abs(),
+			Curve::Tween(ease) => {
+				let u = (t + 0.25).fract() * 2.;
+				
+				if u < 1. {
+					ease.sample(u) * 2. - 1.
+				} else {
+					1. - ease.sample(u - 1.) * 2.
+				}
+			}
+		}
+	}
+}
+
+/// How an obstacle's rocks move, stepped on the physics tick so a run plays
+/// out the same whatever the frame rate
+#[derive(Copy, Clone, Debug)]
+pub struct Motion {
+	pub movement  : Movement,
+	pub curve     : Curve,
+	/// Furthest a rock moves from where it was put, in pixels
+	pub amplitude : f32,
+	/// Seconds to move there and back
+	pub period    : f32,
+}
+
+impl Motion {
+	/// How far a rock on the `orientation` side of a gap has moved up,
+	/// `ticks` physics ticks after it spawned
+	pub fn offset (&self, orientation : Orientation, ticks : u32) -> f32 {
+		let travel = self.curve.wave(ticks as f32 * TIMESTEP / self.period) * self.amplitude;
+		
+		match (self.movement, orientation) {
+			(Movement::Still, _) => 0.,
+			(Movement::Shift, _) | (Movement::Breathe, Orientation::Down) => travel,
+			(Movement::Breathe, Orientation::Up) => -travel,
+		}
+	}
+	
+	/// `gap` as it is `ticks` physics ticks after it spawned
+	pub fn gap (&self, gap : Gap, ticks : u32) -> Gap {
+		Gap {
+			top: gap.top + self.offset(Orientation::Down, ticks),
+			bottom: gap.bottom + self.offset(Orientation::Up, ticks),
+		}
+	}
+}
+
+// Components
+// =========================================================================
+
+/// A rock that moves with its obstacle's `Motion`
+#[derive(Component, Copy, Clone)]
+pub struct RockMotion {
+	pub motion      : Motion,
+	pub orientation : Orientation,
+	/// Where the rock was put, relative to its obstacle
+	pub base        : f32,
+	/// Physics ticks since the rock spawned
+	pub ticks       : u32,
+}
+
+// Systems
+// =========================================================================
+
+pub fn move_rocks (
+	mut query : Query<(&mut Transform, &mut RockMotion)>,
+) {
+	for (mut transform, mut rock) in &mut query {
+		rock.ticks += 1;
+		transform.translation.y = rock.base + rock.motion.offset(rock.orientation, rock.ticks);
+	}
+}
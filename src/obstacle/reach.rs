@@ -0,0 +1,328 @@
+use std::collections::BTreeMap;
+use bevy::prelude::*;
+use crate::level::{CEILING_POSITION, GROUND_POSITION, PLANE_COLLIDER, PLANE_COLLIDER_OFFSET, PLANE_OFFSET, PLANE_START};
+use crate::obstacle::{ObstacleCatalogue, ObstacleSpawner, ROCK_JITTER, SPAWN_OFFSET};
+use crate::obstacle::motion::Motion;
+use crate::physics::{apply_flap, FlapInput, PhysicsConfig, step_flight, TIMESTEP};
+
+/// The underside of the ceiling's collider and the top of the ground's
+const CEILING : f32 = CEILING_POSITION.y - 15.;
+const GROUND  : f32 = GROUND_POSITION.y + 15.;
+
+/// Velocities closer than this, in pixels per second, are treated as the
+/// same while working out where the plane can be
+const VELOCITY_STEP : f32 = 10.;
+
+/// Heights closer than this, in pixels, are merged into one span
+const HEIGHT_STEP : f32 = 1.;
+
+// Structs
+// =========================================================================
+
+/// The opening between an obstacle's top and bottom rocks, in world space
+#[derive(Copy, Clone, Debug)]
+pub struct Gap {
+	pub top    : f32,
+	pub bottom : f32,
+}
+
+/// Everywhere the plane could be at the start of a tick, grouped by how fast
+/// it's climbing or falling
+#[derive(Clone)]
+pub struct Envelope {
+	pub tick : u32,
+	bands    : BTreeMap<i32, Band>,
+}
+
+#[derive(Clone)]
+struct Band {
+	velocity : f32,
+	/// Heights the plane's centre can be at, lowest first and not overlapping
+	spans    : Vec<(f32, f32)>,
+}
+
+impl Band {
+	fn merge (&mut self) {
+		self.spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+		
+		let mut merged : Vec<(f32, f32)> = Vec::with_capacity(self.spans.len());
+		
+		for &(lo, hi) in &self.spans {
+			match merged.last_mut() {
+				Some(last) if lo <= last.1 + HEIGHT_STEP => last.1 = last.1.max(hi),
+				_ => merged.push((lo, hi)),
+			}
+		}
+		
+		self.spans = merged;
+	}
+}
+
+impl Envelope {
+	/// Where the plane starts a level, before it has moved
+	pub fn start () -> Self {
+		let y = PLANE_START.y + PLANE_OFFSET.y;
+		
+		Envelope {
+			tick: 0,
+			bands: BTreeMap::from([(0, Band { velocity: 0., spans: vec![(y, y)] })]),
+		}
+	}
+	
+	/// There's nowhere the plane could be, so it can't have got this far
+	pub fn is_empty (&self) -> bool {
+		self.bands.is_empty()
+	}
+	
+	/// The lowest and highest the plane's centre could be
+	pub fn heights (&self) -> Option<(f32, f32)> {
+		self.bands.values()
+			.flat_map(|band| band.spans.iter())
+			.fold(None, |range, &(lo, hi)| match range {
+				Some((min, max)) => Some((lo.min(min), hi.max(max))),
+				None => Some((lo, hi)),
+			})
+	}
+}
+
+/// A gap, and the ticks its rocks are alongside the plane
+#[derive(Copy, Clone)]
+struct Scheduled {
+	first   : u32,
+	last    : u32,
+	gap     : Gap,
+	/// The tick the gap's obstacle spawned, and how its rocks move since
+	spawned : u32,
+	motion  : Option<Motion>,
+}
+
+impl Scheduled {
+	/// Where the gap is on `tick`. A moving gap is narrowed to wherever it
+	/// is both then and a tick later, since the plane sweeps across the tick.
+	fn gap (&self, tick : u32) -> Gap {
+		let Some(motion) = self.motion else { return self.gap; };
+		let age = tick.saturating_sub(self.spawned);
+		let (now, next) = (motion.gap(self.gap, age), motion.gap(self.gap, age + 1));
+		
+		Gap {
+			top: now.top.min(next.top),
+			bottom: now.bottom.max(next.bottom),
+		}
+	}
+}
+
+/// How the plane and a level's obstacles move, for working out where the
+/// plane can get to. The plane is treated as flying level, so anything it
+/// can't reach it certainly can't reach tilted.
+#[derive(Clone)]
+pub struct Flight {
+	config : PhysicsConfig,
+	/// How far obstacles scroll each tick
+	scroll : f32,
+	/// Half the plane collider's size
+	extent : Vec2,
+	/// Where the middle of the plane's collider is across the screen
+	x      : f32,
+	/// Half the width of the column a rock blocks: half the widest rock,
+	/// plus how far `layout` nudges it sideways
+	rock   : f32,
+}
+
+impl Flight {
+	pub fn new (config : &PhysicsConfig, catalogue : &ObstacleCatalogue, spawner : &ObstacleSpawner) -> Self {
+		Flight {
+			config: config.clone(),
+			scroll: spawner.speed * TIMESTEP,
+			extent: PLANE_COLLIDER * 0.5,
+			x: PLANE_START.x + PLANE_OFFSET.x + PLANE_COLLIDER_OFFSET.x,
+			rock: catalogue.rock_half_width() + ROCK_JITTER,
+		}
+	}
+	
+	/// The first and last ticks a column `offset` across from an obstacle
+	/// spawned on tick `spawned` is alongside the plane
+	pub fn window (&self, spawned : u32, offset : f32) -> (u32, u32) {
+		let reach = self.rock + self.extent.x;
+		let arrive = (SPAWN_OFFSET + offset - self.x - reach) / self.scroll;
+		let leave = (SPAWN_OFFSET + offset - self.x + reach) / self.scroll;
+		
+		(spawned + arrive.floor() as u32 + 1, spawned + leave.ceil() as u32 - 1)
+	}
+	
+	/// The heights the plane's centre can be at on `tick` without touching
+	/// the ceiling, the ground or any rocks alongside it
+	fn corridor (&self, tick : u32, ahead : &[Scheduled]) -> (f32, f32) {
+		let mut low = GROUND + self.extent.y;
+		let mut high = CEILING - self.extent.y;
+		
+		for scheduled in ahead.iter().filter(|s| (s.first..=s.last).contains(&tick)) {
+			let gap = scheduled.gap(tick);
+			low = low.max(gap.bottom + self.extent.y);
+			high = high.min(gap.top - self.extent.y);
+		}
+		
+		(low, high)
+	}
+	
+	/// Where the plane could be a tick after `envelope`, flapping or not,
+	/// without leaving the corridor between `low` and `high`
+	pub fn step (&self, envelope : &Envelope, (low, high) : (f32, f32)) -> Envelope {
+		let mut bands : BTreeMap<i32, Band> = BTreeMap::new();
+		
+		for band in envelope.bands.values() {
+			for flap in [false, true] {
+				let input = FlapInput { pressed: flap, held: flap };
+				let mut velocity = Vec2::new(0., band.velocity);
+				let mut transform = Transform::IDENTITY;
+				
+				apply_flap(&self.config, &input, &mut velocity, TIMESTEP);
+				step_flight(&self.config, &mut velocity, &mut transform, TIMESTEP);
+				
+				let rise = transform.translation.y;
+				let key = (velocity.y / VELOCITY_STEP).round() as i32;
+				let next = bands.entry(key).or_insert(Band {
+					velocity: velocity.y,
+					spans: Vec::new(),
+				});
+				
+				// Collisions are swept, so the plane has to be inside the
+				// corridor both where it starts and where it ends the tick
+				for &(lo, hi) in &band.spans {
+					let lo = lo.max(low).max(low - rise);
+					let hi = hi.min(high).min(high - rise);
+					
+					if lo <= hi {
+						next.spans.push((lo + rise, hi + rise));
+					}
+				}
+			}
+		}
+		
+		bands.retain(|_, band| {
+			band.merge();
+			!band.spans.is_empty()
+		});
+		
+		Envelope { tick: envelope.tick + 1, bands }
+	}
+	
+	/// Steps `envelope` on until `tick`, or until there's nowhere left for
+	/// the plane to be
+	fn advance (&self, mut envelope : Envelope, tick : u32, ahead : &[Scheduled]) -> Envelope {
+		while envelope.tick < tick && !envelope.is_empty() {
+			envelope = self.step(&envelope, self.corridor(envelope.tick, ahead));
+		}
+		
+		envelope
+	}
+}
+
+// Resources
+// =========================================================================
+
+/// Checks a level's obstacles as they're generated, against everywhere the
+/// plane could have got to by the time it reaches each one
+#[derive(Resource, Clone)]
+pub struct CourseCheck {
+	flight   : Flight,
+	/// Where the plane could be, up to where the latest obstacle reaches it
+	envelope : Envelope,
+	/// Obstacles the plane might not have passed by then
+	ahead    : Vec<Scheduled>,
+}
+
+impl CourseCheck {
+	pub fn new (config : &PhysicsConfig, catalogue : &ObstacleCatalogue, spawner : &ObstacleSpawner) -> Self {
+		CourseCheck {
+			flight: Flight::new(config, catalogue, spawner),
+			envelope: Envelope::start(),
+			ahead: Vec::new(),
+		}
+	}
+	
+	/// The lowest and highest the plane could be as a column `offset` across
+	/// from an obstacle spawned on tick `spawned` reaches it, if it can get
+	/// that far at all
+	pub fn reach (&mut self, spawned : u32, offset : f32) -> Option<(f32, f32)> {
+		let (first, _) = self.flight.window(spawned, offset);
+		self.settle(first);
+		self.envelope.heights()
+	}
+	
+	/// Adds the gaps of an obstacle spawned on tick `spawned`, each with how
+	/// far across it is and moving with `motion`, returning whether the plane
+	/// can fly through them all. If it can't they're left out, so the
+	/// obstacles after are judged on their own.
+	pub fn add (&mut self, spawned : u32, gaps : &[(f32, Gap)], motion : Option<Motion>) -> bool {
+		let scheduled : Vec<_> = gaps.iter().map(|&(offset, gap)| {
+			let (first, last) = self.flight.window(spawned, offset);
+			Scheduled { first, last, gap, spawned, motion }
+		}).collect();
+		
+		let Some(first) = scheduled.iter().map(|s| s.first).min() else { return true; };
+		let last = scheduled.iter().map(|s| s.last).max().unwrap_or(first);
+		
+		self.settle(first);
+		
+		let mut ahead = self.ahead.clone();
+		ahead.extend(scheduled);
+		
+		if self.flight.advance(self.envelope.clone(), last + 1, &ahead).is_empty() {
+			return false;
+		}
+		
+		self.ahead = ahead;
+		true
+	}
+	
+	/// Moves where the plane could be on to `tick`, where the next obstacle
+	/// arrives. Obstacles spawned later reach the plane later, so nothing
+	/// can change where it could be before then.
+	fn settle (&mut self, tick : u32) {
+		self.envelope = self.flight.advance(self.envelope.clone(), tick, &self.ahead);
+		self.ahead.retain(|scheduled| scheduled.last >= tick);
+	}
+}
+
+// Tests
+// =========================================================================
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::BASE_LEVEL;
+	
+	fn check () -> CourseCheck {
+		CourseCheck::new(&PhysicsConfig::default(), &ObstacleCatalogue::default(), &BASE_LEVEL.spawner)
+	}
+	
+	/// A gap `height` tall, centred on where the plane starts
+	fn level_gap (height : f32) -> Gap {
+		let y = PLANE_START.y + PLANE_OFFSET.y;
+		Gap { top: y + height * 0.5, bottom: y - height * 0.5 }
+	}
+	
+	#[test]
+	fn passes_wide_gap () {
+		assert!(check().add(0, &[(0., level_gap(300.))], None));
+	}
+	
+	#[test]
+	fn rejects_gap_narrower_than_plane () {
+		assert!(!check().add(0, &[(0., level_gap(PLANE_COLLIDER.y * 0.5))], None));
+	}
+	
+	#[test]
+	fn rejects_gap_in_ceiling () {
+		let gap = Gap { top: CEILING + 200., bottom: CEILING - 10. };
+		assert!(!check().add(0, &[(0., gap)], None));
+	}
+	
+	#[test]
+	fn leaves_out_rejected_gaps () {
+		let mut check = check();
+		
+		assert!(!check.add(0, &[(0., level_gap(10.))], None));
+		assert!(check.add(0, &[(0., level_gap(300.))], None));
+	}
+}
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use super::geometry::{aabb, bounds};
+
+// Structs
+// =========================================================================
+
+/// A collider's world-space shape, cached along with its bounds
+pub struct Proxy {
+	pub entity : Entity,
+	pub min    : Vec2,
+	pub max    : Vec2,
+	pub points : Vec<Vec2>,
+	/// How far the collider moved on the last tick
+	pub motion : Vec2,
+}
+
+// Resources
+// =========================================================================
+
+/// Sweep and prune broadphase. Proxies are kept sorted by their left edge, so
+/// a query only looks at colliders whose x range could overlap it.
+#[derive(Resource, Default)]
+pub struct Broadphase {
+	proxies    : Vec<Proxy>,
+	/// Where each entity's proxy is in `proxies`
+	slots      : HashMap<Entity, usize>,
+	max_width  : f32,
+	/// The furthest any proxy moved on the last tick, along each axis
+	max_motion : Vec2,
+}
+
+impl Broadphase {
+	/// Marks every proxy as still, before a tick's updates
+	pub fn start_tick (&mut self) {
+		for proxy in &mut self.proxies {
+			proxy.motion = Vec2::ZERO;
+		}
+	}
+	
+	/// Sets the shape of an entity's collider, adding it if it's new
+	pub fn update (&mut self, entity : Entity, points : Vec<Vec2>) {
+		let (min, max) = bounds(&points);
+		
+		match self.slots.get(&entity) {
+			Some(&slot) => {
+				let proxy = &mut self.proxies[slot];
+				proxy.motion = min - proxy.min;
+				proxy.min = min;
+				proxy.max = max;
+				proxy.points = points;
+			}
+			None => {
+				self.slots.insert(entity, self.proxies.len());
+				self.proxies.push(Proxy { entity, min, max, points, motion: Vec2::ZERO });
+			}
+		}
+	}
+	
+	/// Drops the proxies of entities `keep` returns false for. Call after
+	/// updating and before `build`.
+	pub fn retain (&mut self, mut keep : impl FnMut(Entity) -> bool) {
+		self.proxies.retain(|proxy| keep(proxy.entity));
+	}
+	
+	/// Sorts the proxies, must be called after changing them and before
+	/// querying. Moved proxies are rarely far out of order, so this is close
+	/// to linear.
+	pub fn build (&mut self) {
+		self.proxies.sort_by(|a, b| a.min.x.total_cmp(&b.min.x));
+		
+		self.max_width = self.proxies.iter()
+			.map(|proxy| proxy.max.x - proxy.min.x)
+			.fold(0., f32::max);
+		
+		self.max_motion = self.proxies.iter()
+			.map(|proxy| proxy.motion.abs())
+			.fold(Vec2::ZERO, Vec2::max);
+		
+		self.slots.clear();
+		self.slots.extend(self.proxies.iter().enumerate().map(|(slot, proxy)| (proxy.entity, slot)));
+	}
+	
+	/// The furthest any proxy moved on the last tick, along each axis
+	pub fn max_motion (&self) -> Vec2 {
+		self.max_motion
+	}
+	
+	/// Every proxy whose bounds overlap the given bounds
+	pub fn query (&self, min : Vec2, max : Vec2) -> impl Iterator<Item = &Proxy> {
+		// Nothing starting further left than the widest proxy can reach `min`
+		let start = self.proxies.partition_point(|p| p.min.x < min.x - self.max_width);
+		let end = self.proxies.partition_point(|p| p.min.x <= max.x);
+		
+		self.proxies[start..end]
+			.iter()
+			.filter(move |p| aabb(min, max, p.min, p.max))
+	}
+}
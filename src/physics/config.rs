@@ -0,0 +1,140 @@
+use bevy::asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use serde::Deserialize;
+
+// Resources
+// =========================================================================
+
+/// Tuning for how the plane flies. Loaded from `plane.physics.ron` and kept in
+/// sync with it, so the feel can be changed without recompiling.
+#[derive(Resource, Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "547de807-a779-4949-ac54-35233c3d1560"]
+#[serde(default)]
+pub struct PhysicsConfig {
+	/// How flap input moves the plane
+	pub model         : FlightModel,
+	/// Downward acceleration, in pixels per second squared
+	pub gravity       : f32,
+	/// Vertical velocity set by a flap
+	pub flap_velocity : f32,
+	/// How far the plane tilts while climbing or falling, in degrees
+	pub tilt          : f32,
+	/// How quickly the plane turns towards its tilt
+	pub tilt_speed    : f32,
+	/// How the plane tumbles after crashing
+	pub crash         : CrashConfig,
+}
+
+impl Default for PhysicsConfig {
+	fn default () -> Self {
+		PhysicsConfig {
+			model: FlightModel::Classic,
+			gravity: -800.,
+			flap_velocity: 300.,
+			tilt: 30.,
+			tilt_speed: 1.,
+			crash: CrashConfig::default(),
+		}
+	}
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CrashConfig {
+	/// Spin the plane starts tumbling with, in degrees per second
+	pub spin        : f32,
+	/// How much of its speed into the ground the plane keeps when bouncing
+	pub restitution : f32,
+	/// Fraction of sliding speed and spin lost on each bounce
+	pub friction    : f32,
+	/// Below this speed a plane on the ground stops moving
+	pub rest_speed  : f32,
+}
+
+impl Default for CrashConfig {
+	fn default () -> Self {
+		CrashConfig {
+			spin: 360.,
+			restitution: 0.4,
+			friction: 0.3,
+			rest_speed: 20.,
+		}
+	}
+}
+
+/// How flap input moves the plane. Game modes pick one through their config.
+#[derive(Deserialize, Copy, Clone, Debug)]
+pub enum FlightModel {
+	/// Every tap sets the plane climbing at `flap_velocity`
+	Classic,
+	/// Holding flap accelerates the plane upwards, while air drag slows it
+	/// and its fall is capped
+	Thrust {
+		/// Upward acceleration while flap is held
+		thrust            : f32,
+		/// Fraction of the plane's velocity lost per second
+		drag              : f32,
+		/// Fastest the plane can fall
+		terminal_velocity : f32,
+	},
+}
+
+/// The config asset currently in use. Swap the handle to give a level or
+/// plane its own tuning.
+#[derive(Resource, Default)]
+pub struct PhysicsConfigHandle (pub Handle<PhysicsConfig>);
+
+// Loader
+// =========================================================================
+
+#[derive(Default)]
+pub struct PhysicsConfigLoader;
+
+impl AssetLoader for PhysicsConfigLoader {
+	fn load<'a> (
+		&'a self,
+		bytes : &'a [u8],
+		load_context : &'a mut LoadContext,
+	) -> BoxedFuture<'a, Result<(), Error>> {
+		Box::pin(async move {
+			let config = ron::de::from_bytes::<PhysicsConfig>(bytes)?;
+			load_context.set_default_asset(LoadedAsset::new(config));
+			Ok(())
+		})
+	}
+	
+	fn extensions (&self) -> &[&str] {
+		&["physics.ron"]
+	}
+}
+
+// Systems
+// =========================================================================
+
+pub fn load_config (
+	asset_server : Res<AssetServer>,
+	mut handle : ResMut<PhysicsConfigHandle>,
+) {
+	handle.0 = asset_server.load("plane.physics.ron");
+}
+
+pub fn apply_config (
+	mut reader : EventReader<AssetEvent<PhysicsConfig>>,
+	assets : Res<Assets<PhysicsConfig>>,
+	handle : Res<PhysicsConfigHandle>,
+	mut config : ResMut<PhysicsConfig>,
+) {
+	for event in reader.iter() {
+		match event {
+			AssetEvent::Created { handle: h } | AssetEvent::Modified { handle: h } => {
+				if *h != handle.0 { continue; }
+				
+				if let Some(loaded) = assets.get(h) {
+					*config = loaded.clone();
+				}
+			}
+			AssetEvent::Removed { .. } => {}
+		}
+	}
+}
@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+
+// Structs
+// =========================================================================
+
+/// The contact manifold of two overlapping convex shapes, from the point of
+/// view of the first shape tested (the player, in collision events)
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(not(feature = "debug"), allow(dead_code))]
+pub struct Contact {
+	/// Unit axis of least overlap, pointing out of the other shape
+	pub normal : Vec2,
+	/// How far the shapes overlap along `normal`
+	pub depth  : f32,
+	/// The minimum translation that separates the shapes (`normal * depth`)
+	pub mtv    : Vec2,
+	/// The deepest point of the first shape inside the other
+	pub point  : Vec2,
+}
+
+// Helpers
+// =========================================================================
+
+pub fn aabb (a_min : Vec2, a_max : Vec2, b_min : Vec2, b_max : Vec2) -> bool {
+	   a_min.x < b_max.x
+	&& a_max.x > b_min.x
+	&& a_min.y < b_max.y
+	&& a_max.y > b_min.y
+}
+
+pub fn bounds (points : &[Vec2]) -> (Vec2, Vec2) {
+	points.iter().fold(
+		(Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+		|(min, max), p| (min.min(*p), max.max(*p)),
+	)
+}
+
+pub fn rect_points (min : Vec2, max : Vec2) -> Vec<Vec2> {
+	vec![
+		min,
+		Vec2::new(min.x, max.y),
+		max,
+		Vec2::new(max.x, min.y),
+	]
+}
+
+/// Monotone chain convex hull, wound counter-clockwise
+pub fn convex_hull (points : &[Vec2]) -> Vec<Vec2> {
+	let mut points = points.to_vec();
+	points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+	
+	let mut hull : Vec<Vec2> = Vec::with_capacity(points.len() * 2);
+	
+	for pass in 0..=1 {
+		let start = hull.len();
+		
+		for p in &points {
+			while hull.len() >= start + 2
+				&& (hull[hull.len() - 1] - hull[hull.len() - 2]).perp_dot(*p - hull[hull.len() - 2]) <= 0.
+			{
+				hull.pop();
+			}
+			
+			hull.push(*p);
+		}
+		
+		hull.pop();
+		
+		if pass == 0 {
+			points.reverse();
+		}
+	}
+	
+	hull
+}
+
+pub fn sat (
+	a : &[Vec2],
+	b : &[Vec2],
+) -> Option<Contact> {
+	debug_assert!(a.len() > 2, "a must have at least 3 points");
+	debug_assert!(b.len() > 2, "b must have at least 3 points");
+	
+	let mut poly_1 = a;
+	let mut poly_2 = b;
+	
+	let mut depth = f32::MAX;
+	let mut normal = Vec2::ZERO;
+	
+	for i in 0..=1 {
+		if i == 1 {
+			poly_1 = b;
+			poly_2 = a;
+		}
+		
+		for a in 0..poly_1.len() {
+			let b = (a + 1) % poly_1.len();
+			
+			let axis_proj = Vec2::new(
+				-(poly_1[b].y - poly_1[a].y),
+				poly_1[b].x - poly_1[a].x,
+			).normalize_or_zero();
+			
+			if axis_proj == Vec2::ZERO {
+				continue;
+			}
+			
+			let mut min_r1 = f32::MAX;
+			let mut max_r1 = f32::MIN;
+			
+			for p in poly_1 {
+				let q = p.dot(axis_proj);
+				min_r1 = f32::min(min_r1, q);
+				max_r1 = f32::max(max_r1, q);
+			}
+			
+			let mut min_r2 = f32::MAX;
+			let mut max_r2 = f32::MIN;
+			
+			for p in poly_2 {
+				let q = p.dot(axis_proj);
+				min_r2 = f32::min(min_r2, q);
+				max_r2 = f32::max(max_r2, q);
+			}
+			
+			if !(max_r2 >= min_r1 && max_r1 >= min_r2) {
+				return None;
+			}
+			
+			let overlap = f32::min(max_r1, max_r2) - f32::max(min_r1, min_r2);
+			
+			if overlap < depth {
+				depth = overlap;
+				normal = axis_proj;
+			}
+		}
+	}
+	
+	// Point the normal out of `b`, towards `a`
+	if (centroid(a) - centroid(b)).dot(normal) < 0. {
+		normal = -normal;
+	}
+	
+	// The point of `a` furthest into `b`
+	let point = a.iter().copied().fold(a[0], |deepest, p| {
+		if p.dot(normal) < deepest.dot(normal) { p } else { deepest }
+	});
+	
+	Some(Contact {
+		normal,
+		depth,
+		mtv: normal * depth,
+		point,
+	})
+}
+
+pub fn centroid (points : &[Vec2]) -> Vec2 {
+	points.iter().copied().sum::<Vec2>() / points.len() as f32
+}
+
+// Tests
+// =========================================================================
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn square (min : Vec2, size : f32) -> Vec<Vec2> {
+		rect_points(min, min + Vec2::splat(size))
+	}
+	
+	#[test]
+	fn sat_finds_least_overlap () {
+		let a = square(Vec2::ZERO, 2.);
+		let b = square(Vec2::new(1.5, -1.), 2.);
+		let contact = sat(&a, &b).unwrap();
+		
+		assert!((contact.depth - 0.5).abs() < 1e-5);
+		assert!((contact.normal - Vec2::NEG_X).length() < 1e-5);
+		assert!((contact.mtv - Vec2::new(-0.5, 0.)).length() < 1e-5);
+		assert_eq!(contact.point.x, 2.);
+	}
+	
+	#[test]
+	fn sat_misses_separated_shapes () {
+		let a = square(Vec2::ZERO, 2.);
+		
+		assert!(sat(&a, &square(Vec2::new(3., 0.), 2.)).is_none());
+		assert!(sat(&a, &square(Vec2::new(0., -2.5), 2.)).is_none());
+	}
+	
+	#[test]
+	fn hull_drops_inner_points () {
+		let mut points = square(Vec2::ZERO, 2.);
+		points.extend([Vec2::ONE, Vec2::new(1., 0.), Vec2::new(0.5, 1.5)]);
+		
+		let hull = convex_hull(&points);
+		
+		assert_eq!(hull.len(), 4);
+		
+		for corner in square(Vec2::ZERO, 2.) {
+			assert!(hull.contains(&corner));
+		}
+		
+		// Counter-clockwise, so every turn is to the left
+		for i in 0..hull.len() {
+			let (a, b, c) = (hull[i], hull[(i + 1) % 4], hull[(i + 2) % 4]);
+			assert!((b - a).perp_dot(c - b) > 0.);
+		}
+	}
+}
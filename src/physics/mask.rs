@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use super::geometry::convex_hull;
+
+/// Pixels at or above this alpha are solid
+const ALPHA_THRESHOLD : u8 = 128;
+
+/// Builds a set of convex hulls covering the solid pixels in `rect` of an
+/// RGBA8 image `width` pixels wide. The region is cut into horizontal bands
+/// `band` pixels tall and each band gets its own hull, so the set follows
+/// concave and jagged outlines. Points are relative to the centre of `rect`,
+/// with y up, to line up with a sprite drawn from that region.
+pub fn alpha_hulls (
+	data  : &[u8],
+	width : usize,
+	rect  : Rect,
+	band  : usize,
+) -> Vec<Vec<Vec2>> {
+	let x0 = rect.min.x.floor() as usize;
+	let x1 = rect.max.x.ceil() as usize;
+	let y0 = rect.min.y.floor() as usize;
+	let y1 = rect.max.y.ceil() as usize;
+	
+	let centre = rect.center();
+	let to_local = |x : usize, y : usize| Vec2::new(
+		x as f32 - centre.x,
+		centre.y - y as f32,
+	);
+	
+	let mut hulls = Vec::new();
+	
+	for band_y in (y0..y1).step_by(band.max(1)) {
+		let mut points = Vec::new();
+		
+		for y in band_y..(band_y + band).min(y1) {
+			let row = &data[(y * width + x0) * 4 .. (y * width + x1) * 4];
+			let solid = |x : &usize| row[x * 4 + 3] >= ALPHA_THRESHOLD;
+			
+			let Some(left) = (0..x1 - x0).find(solid) else {
+				continue;
+			};
+			let right = (0..x1 - x0).rfind(solid).unwrap() + 1;
+			
+			// Pixel corners, so a single solid row still has an area
+			points.push(to_local(x0 + left, y));
+			points.push(to_local(x0 + right, y));
+			points.push(to_local(x0 + left, y + 1));
+			points.push(to_local(x0 + right, y + 1));
+		}
+		
+		if points.is_empty() {
+			continue;
+		}
+		
+		hulls.push(simplify(convex_hull(&points), 0.5));
+	}
+	
+	hulls
+}
+
+/// Drops points of a convex polygon that sit within `tolerance` of the line
+/// between their neighbours
+fn simplify (mut points : Vec<Vec2>, tolerance : f32) -> Vec<Vec2> {
+	let mut i = 0;
+	
+	while points.len() > 3 && i < points.len() {
+		let prev = points[(i + points.len() - 1) % points.len()];
+		let next = points[(i + 1) % points.len()];
+		let edge = next - prev;
+		let distance = edge.perp_dot(points[i] - prev).abs() / edge.length();
+		
+		if distance < tolerance {
+			points.remove(i);
+		} else {
+			i += 1;
+		}
+	}
+	
+	points
+}
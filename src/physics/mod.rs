@@ -0,0 +1,534 @@
+pub mod broadphase;
+mod config;
+pub mod geometry;
+mod mask;
+
+use bevy::prelude::*;
+use bevy::transform::systems::{propagate_transforms, sync_simple_transforms};
+use crate::{AppState, GameState};
+use crate::actions::Action;
+use crate::physics::broadphase::*;
+use crate::physics::config::*;
+use crate::physics::geometry::*;
+
+pub use config::{PhysicsConfig, PhysicsConfigHandle};
+pub use mask::alpha_hulls;
+
+/// Length of a physics tick, in seconds
+pub const TIMESTEP : f32 = 1. / 60.;
+
+/// Furthest apart the plane's poses are when looking for where it first
+/// touched a collider it passed through, in pixels
+const CONTACT_STEP : f32 = 2.;
+
+// Collision layers
+// -------------------------------------------------------------------------
+
+pub mod layer {
+	pub const PLAYER   : u32 = 1 << 0;
+	pub const WORLD    : u32 = 1 << 1;
+	pub const OBSTACLE : u32 = 1 << 2;
+	pub const ALL      : u32 = u32::MAX;
+}
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.insert_resource(FixedTime::new_from_secs(TIMESTEP))
+			.init_resource::<PhysicsConfig>()
+			.init_resource::<PhysicsConfigHandle>()
+			.add_asset::<PhysicsConfig>()
+			.init_asset_loader::<PhysicsConfigLoader>()
+			.init_resource::<FlapInput>()
+			.init_resource::<PhysicsTick>()
+			.init_resource::<Broadphase>()
+			.add_event::<CollisionEvent>()
+			.edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+				schedule.configure_set(
+					PhysicsSet
+						.run_if(in_state(AppState::Game))
+						.run_if(in_state(GameState::Play))
+				);
+				schedule.configure_set(FlapInputSet.in_set(PhysicsSet));
+			})
+			.add_startup_system(load_config)
+			.add_system(reset_tick.in_schedule(OnEnter(AppState::Game)))
+			.add_system(apply_config)
+			.add_system(
+				read_input
+					.in_set(OnUpdate(AppState::Game))
+					.in_set(OnUpdate(GameState::Play))
+			)
+			.add_systems(
+				(
+					input.after(FlapInputSet),
+					apply_velocity,
+					// Obstacles move on the fixed step too, so collide against
+					// where they are this tick rather than last frame
+					sync_simple_transforms,
+					propagate_transforms,
+					update_broadphase,
+					resolve_collisions,
+					advance_tick,
+				).chain()
+				 .in_set(PhysicsSet)
+				 .in_schedule(CoreSchedule::FixedUpdate)
+			)
+			.add_system(start_crash.in_schedule(OnEnter(GameState::Dead)))
+			.add_system(
+				crash
+					.run_if(in_state(AppState::Game))
+					.run_if(in_state(GameState::Dead))
+					.in_schedule(CoreSchedule::FixedUpdate)
+			)
+			.add_system(
+				interpolate
+					.in_set(OnUpdate(AppState::Game))
+					.run_if(in_state(GameState::Play).or_else(in_state(GameState::Dead)))
+			)
+		;
+	}
+}
+
+// Sets
+// =========================================================================
+
+/// Systems stepped on the fixed timestep while the game is being played
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhysicsSet;
+
+/// Fixed step systems that decide the tick's `FlapInput`, before the plane
+/// reacts to it
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FlapInputSet;
+
+// Resources
+// =========================================================================
+
+/// Flap input, read every frame but only consumed on the fixed timestep, so
+/// no press is lost or doubled
+#[derive(Resource, Default)]
+pub struct FlapInput {
+	/// A flap was pressed since the last physics tick
+	pub pressed : bool,
+	/// Flap is being held down
+	pub held    : bool,
+}
+
+/// Physics ticks simulated since the level started
+#[derive(Resource, Default)]
+pub struct PhysicsTick (pub u32);
+
+// Events
+// =========================================================================
+
+/// Sent whenever the player overlaps a collider
+pub struct CollisionEvent {
+	pub player  : Entity,
+	pub other   : Entity,
+	pub kind    : CollisionKind,
+	pub contact : Contact,
+	/// The other collider is a sensor, so this is an overlap and not a hit
+	pub sensor  : bool,
+}
+
+// Components
+// =========================================================================
+
+/// What a collider is, so game rules can decide how to respond to a hit
+#[derive(Component, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CollisionKind {
+	Ground,
+	Ceiling,
+	Rock,
+}
+
+/// Which layers a collider is on and which it can touch. Colliders without a
+/// filter are on every layer and touch everything.
+#[derive(Component, Copy, Clone)]
+pub struct CollisionFilter {
+	pub layers : u32,
+	pub mask   : u32,
+	/// Sensors report overlaps but are never treated as hits
+	pub sensor : bool,
+}
+
+impl Default for CollisionFilter {
+	fn default () -> Self {
+		CollisionFilter {
+			layers: layer::ALL,
+			mask: layer::ALL,
+			sensor: false,
+		}
+	}
+}
+
+impl CollisionFilter {
+	pub fn interacts (&self, other : &CollisionFilter) -> bool {
+		self.mask & other.layers != 0 && other.mask & self.layers != 0
+	}
+}
+
+#[derive(Component, Default)]
+pub struct Velocity (pub Vec2);
+
+/// Spin in radians per second, used while the plane is tumbling after a crash
+#[derive(Component, Default)]
+pub struct AngularVelocity (pub f32);
+
+/// The simulated transform of a body at the last two physics ticks. The
+/// rendered `Transform` is interpolated between them every frame.
+#[derive(Component)]
+pub struct PhysicsTransform {
+	pub previous : Transform,
+	pub current  : Transform,
+}
+
+impl PhysicsTransform {
+	pub fn new (transform : Transform) -> Self {
+		PhysicsTransform {
+			previous: transform,
+			current: transform,
+		}
+	}
+}
+
+#[derive(Component)]
+pub struct AABBCollider(pub Vec2, pub Option<Vec2>);
+
+impl AABBCollider {
+	pub fn bounds (&self, transform : &GlobalTransform) -> (Vec2, Vec2) {
+		let half = self.0 * 0.5;
+		let pos = transform.translation().truncate() + self.1.unwrap_or(Vec2::ZERO);
+		
+		(pos - half, pos + half)
+	}
+}
+
+/// A box collider that rotates with its entity
+#[derive(Component)]
+pub struct OBBCollider(pub Vec2, pub Option<Vec2>);
+
+impl OBBCollider {
+	pub fn points (&self, transform : &GlobalTransform) -> Vec<Vec2> {
+		let half = self.0 * 0.5;
+		let offset = self.1.unwrap_or(Vec2::ZERO);
+		
+		rect_points(offset - half, offset + half)
+			.into_iter()
+			.map(|p| transform.transform_point(p.extend(0.)).truncate())
+			.collect()
+	}
+}
+
+#[derive(Component)]
+pub struct SATCollider (pub Vec<Vec2>);
+
+// Flight
+// =========================================================================
+
+/// Applies a tick's flap input to the plane's velocity
+pub fn apply_flap (
+	config : &PhysicsConfig,
+	flap : &FlapInput,
+	velocity : &mut Vec2,
+	delta : f32,
+) {
+	match config.model {
+		FlightModel::Classic => {
+			if flap.pressed {
+				velocity.y = config.flap_velocity;
+			}
+		}
+		FlightModel::Thrust { thrust, .. } => {
+			if flap.held {
+				velocity.y += thrust * delta;
+			}
+		}
+	}
+}
+
+/// Moves the flying plane on by a tick: falling, and tilting towards the way
+/// it's heading
+pub fn step_flight (
+	config : &PhysicsConfig,
+	velocity : &mut Vec2,
+	transform : &mut Transform,
+	delta : f32,
+) {
+	let velocity = &mut velocity.y;
+	*velocity += config.gravity * delta;
+	
+	if let FlightModel::Thrust { drag, terminal_velocity, .. } = config.model {
+		*velocity -= *velocity * drag * delta;
+		*velocity = velocity.max(-terminal_velocity);
+	}
+	
+	transform.translation.y += *velocity * delta;
+	let rot = config.tilt * if *velocity > 0. { 1.0_f32 } else { -1.0_f32 };
+	transform.rotation = transform.rotation.slerp(
+		Quat::from_rotation_z(rot.to_radians()),
+		config.tilt_speed * delta,
+	);
+}
+
+// Systems
+// =========================================================================
+
+fn read_input (
+	mut flap : ResMut<FlapInput>,
+	actions : Res<Input<Action>>,
+) {
+	if actions.just_pressed(Action::Flap) {
+		flap.pressed = true;
+	}
+	
+	flap.held = actions.pressed(Action::Flap);
+}
+
+fn reset_tick (mut tick : ResMut<PhysicsTick>) {
+	tick.0 = 0;
+}
+
+fn advance_tick (mut tick : ResMut<PhysicsTick>) {
+	tick.0 += 1;
+}
+
+fn input (
+	mut query : Query<&mut Velocity>,
+	mut flap : ResMut<FlapInput>,
+	fixed_time : Res<FixedTime>,
+	config : Res<PhysicsConfig>,
+) {
+	let mut velocity = query.single_mut();
+	apply_flap(&config, &flap, &mut velocity.0, fixed_time.period.as_secs_f32());
+	flap.pressed = false;
+}
+
+fn apply_velocity (
+	mut query : Query<(&mut Velocity, &mut PhysicsTransform)>,
+	fixed_time : Res<FixedTime>,
+	config : Res<PhysicsConfig>,
+) {
+	let delta = fixed_time.period.as_secs_f32();
+	
+	for (mut velocity, mut body) in query.iter_mut() {
+		body.previous = body.current;
+		step_flight(&config, &mut velocity.0, &mut body.current, delta);
+	}
+}
+
+// Crash
+// -------------------------------------------------------------------------
+
+fn start_crash (
+	mut query : Query<&mut AngularVelocity>,
+	config : Res<PhysicsConfig>,
+) {
+	for mut spin in query.iter_mut() {
+		spin.0 = -config.crash.spin.to_radians();
+	}
+}
+
+/// Lets the crashed plane fall and tumble, bouncing off the ground until it
+/// comes to rest
+fn crash (
+	mut player_query : Query<(&Parent, &mut Velocity, &mut AngularVelocity, &mut PhysicsTransform, &OBBCollider)>,
+	parent_query : Query<&GlobalTransform>,
+	ground_query : Query<(&GlobalTransform, &AABBCollider, &CollisionKind)>,
+	fixed_time : Res<FixedTime>,
+	config : Res<PhysicsConfig>,
+) {
+	let delta = fixed_time.period.as_secs_f32();
+	let crash = &config.crash;
+	
+	for (parent, mut velocity, mut spin, mut body, collider) in player_query.iter_mut() {
+		body.previous = body.current;
+		
+		velocity.0.y += config.gravity * delta;
+		body.current.translation += (velocity.0 * delta).extend(0.);
+		body.current.rotate_z(spin.0 * delta);
+		
+		let parent_transform = parent_query.get(parent.get()).unwrap();
+		let to_local = parent_transform.compute_matrix().inverse();
+		let points = collider.points(&parent_transform.mul_transform(body.current));
+		
+		for (transform, ground, kind) in &ground_query {
+			if *kind != CollisionKind::Ground {
+				continue;
+			}
+			
+			let (min, max) = ground.bounds(transform);
+			
+			let Some(contact) = sat(&points, &rect_points(min, max)) else {
+				continue;
+			};
+			
+			// Push the plane back out of the ground
+			body.current.translation += to_local.transform_vector3(contact.mtv.extend(0.));
+			
+			// Bounce off it, losing speed and spin to friction
+			let into = velocity.0.dot(contact.normal);
+			
+			if into < 0. {
+				let tangent = contact.normal.perp();
+				let slide = velocity.0.dot(tangent);
+				velocity.0 -= contact.normal * into * (1. + crash.restitution);
+				velocity.0 -= tangent * slide * crash.friction;
+				spin.0 *= 1. - crash.friction;
+			}
+			
+			if velocity.0.length() < crash.rest_speed {
+				velocity.0 = Vec2::ZERO;
+				spin.0 = 0.;
+			}
+		}
+	}
+}
+
+// Interpolation
+// -------------------------------------------------------------------------
+
+fn interpolate (
+	mut query : Query<(&mut Transform, &PhysicsTransform)>,
+	fixed_time : Res<FixedTime>,
+) {
+	let alpha = fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32();
+	
+	for (mut transform, body) in query.iter_mut() {
+		transform.translation = body.previous.translation.lerp(body.current.translation, alpha);
+		transform.rotation = body.previous.rotation.slerp(body.current.rotation, alpha);
+	}
+}
+
+fn update_broadphase (
+	mut broadphase : ResMut<Broadphase>,
+	aabb_collider_query : Query<
+		(Entity, &GlobalTransform, &AABBCollider),
+		(Without<Velocity>, Or<(Changed<GlobalTransform>, Changed<AABBCollider>)>),
+	>,
+	sat_collider_query : Query<
+		(Entity, &GlobalTransform, &SATCollider),
+		(Without<Velocity>, Or<(Changed<GlobalTransform>, Changed<SATCollider>)>),
+	>,
+	collider_query : Query<(), (Without<Velocity>, Or<(With<AABBCollider>, With<SATCollider>)>)>,
+) {
+	broadphase.start_tick();
+	
+	// Only colliders that moved or changed shape since the last tick
+	for (entity, transform, collider) in &aabb_collider_query {
+		let (min, max) = collider.bounds(transform);
+		broadphase.update(entity, rect_points(min, max));
+	}
+	
+	for (entity, transform, collider) in &sat_collider_query {
+		let t = transform.translation().truncate();
+		broadphase.update(entity, collider.0.iter().map(|p| *p + t).collect());
+	}
+	
+	// Checked rather than read from `RemovedComponents`, which can be
+	// cleared on a frame without a physics tick
+	broadphase.retain(|entity| collider_query.contains(entity));
+	broadphase.build();
+}
+
+fn resolve_collisions (
+	mut player_query : Query<(Entity, &Parent, &PhysicsTransform, &OBBCollider, Option<&CollisionFilter>), With<Velocity>>,
+	parent_query : Query<&GlobalTransform>,
+	collider_query : Query<(&CollisionKind, Option<&CollisionFilter>)>,
+	broadphase : Res<Broadphase>,
+	mut writer : EventWriter<CollisionEvent>,
+) {
+	let (
+		player,
+		player_parent,
+		player_body,
+		player_collider,
+		player_filter,
+	) = player_query.single_mut();
+	
+	let player_filter = player_filter.copied().unwrap_or_default();
+	
+	// The rendered transform lags behind the simulation, so place the
+	// player from its physics state rather than its `GlobalTransform`
+	let parent_transform = parent_query.get(player_parent.get()).unwrap();
+	
+	let previous = player_collider.points(&parent_transform.mul_transform(player_body.previous));
+	let current = player_collider.points(&parent_transform.mul_transform(player_body.current));
+	
+	// Anything that could have crossed the plane's path this tick
+	let (player_min, player_max) = bounds(&[previous.as_slice(), &current].concat());
+	let reach = broadphase.max_motion();
+	
+	for proxy in broadphase.query(player_min - reach, player_max + reach) {
+		let Ok((kind, filter)) = collider_query.get(proxy.entity) else {
+			continue;
+		};
+		
+		let filter = filter.copied().unwrap_or_default();
+		
+		if !player_filter.interacts(&filter) {
+			continue;
+		}
+		
+		// Test the whole path swept since the last tick, relative to the
+		// collider, so neither a fast plane nor a fast rock can step over
+		// the thin end of a collider between ticks
+		let swept = convex_hull(&[
+			previous.iter().map(|p| *p + proxy.motion).collect(),
+			current.clone(),
+		].concat());
+		
+		if sat(&swept, &proxy.points).is_none() {
+			continue;
+		}
+		
+		// The swept hull only finds the hit. The manifold comes from the
+		// plane's actual shape: where it is now if it's still touching,
+		// otherwise where it first touched on the way through.
+		let travel = (centroid(&current) - centroid(&previous) - proxy.motion).length();
+		let steps = (travel / CONTACT_STEP).ceil().clamp(1., 64.) as u32;
+		
+		let contact = sat(&current, &proxy.points).or_else(|| {
+			(0..steps).find_map(|step| {
+				let t = step as f32 / steps as f32;
+				let points : Vec<_> = pose(player_collider, parent_transform, player_body, t)
+					.into_iter()
+					.map(|p| p + proxy.motion * (1. - t))
+					.collect();
+				
+				sat(&points, &proxy.points)
+			})
+		});
+		
+		// Only the hull between poses touched, closer than `CONTACT_STEP`
+		let Some(contact) = contact else { continue; };
+		
+		writer.send(CollisionEvent {
+			player,
+			other: proxy.entity,
+			kind: *kind,
+			contact,
+			sensor: filter.sensor,
+		});
+	}
+}
+
+/// The points of the plane's collider `t` of the way from its previous
+/// transform to its current one
+fn pose (
+	collider : &OBBCollider,
+	parent : &GlobalTransform,
+	body : &PhysicsTransform,
+	t : f32,
+) -> Vec<Vec2> {
+	let transform = Transform {
+		translation: body.previous.translation.lerp(body.current.translation, t),
+		rotation: body.previous.rotation.slerp(body.current.rotation, t),
+		scale: body.current.scale,
+	};
+	
+	collider.points(&parent.mul_transform(transform))
+}
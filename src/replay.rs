@@ -0,0 +1,482 @@
+use std::fmt;
+use bevy::prelude::*;
+use crate::{AppState, GameState, Level, LevelTheme};
+use crate::autopilot::AutopilotSet;
+use crate::obstacle::ObstacleSpawner;
+use crate::physics::{FlapInput, FlapInputSet, PhysicsTick};
+use crate::rng::GameRng;
+use crate::scenes::Attract;
+use crate::storage::Storage;
+use crate::transitions::TransitionTo;
+
+/// Start of every replay file
+const MAGIC : &[u8; 4] = b"TPRP";
+
+/// Bump whenever the file layout or the simulation changes in a way that
+/// would make old replays play out differently
+const VERSION : u8 = 5;
+
+/// Where the most recent run is saved
+const LAST_REPLAY : &str = "last.replay";
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.init_resource::<Recorder>()
+			.add_startup_system(load_from_args)
+			.add_system(start_recording.in_schedule(OnEnter(AppState::Game)))
+			.add_systems(
+				(
+					record
+						.run_if(not(resource_exists::<Playback>()))
+						.run_if(not(resource_exists::<Attract>())),
+					play_back.run_if(resource_exists::<Playback>()),
+				).in_set(FlapInputSet)
+				 // Records what the autopilot chose too
+				 .after(AutopilotSet)
+				 .in_schedule(CoreSchedule::FixedUpdate)
+			)
+			.add_system(save_recording.in_schedule(OnEnter(GameState::Dead)))
+			.add_system(save_recording.in_schedule(OnEnter(GameState::Exit)))
+			.add_systems((stop_recording, finish_playback).in_schedule(OnExit(AppState::Game)))
+		;
+	}
+}
+
+// Replay
+// =========================================================================
+
+/// The flap input on a physics tick where something changed
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ReplayInput {
+	pub tick    : u32,
+	pub pressed : bool,
+	pub held    : bool,
+}
+
+/// Everything needed to play a level attempt out again: the seed and level it
+/// started from, and the flap input on every tick it changed. Replays assume
+/// the same `plane.physics.ron` they were recorded with.
+#[derive(Clone)]
+pub struct Replay {
+	pub run_seed : u64,
+	pub level    : Level,
+	pub inputs   : Vec<ReplayInput>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+	NotAReplay,
+	UnsupportedVersion(u8),
+	Truncated,
+	Invalid,
+	/// An input's tick comes before the one ahead of it
+	OutOfOrder,
+}
+
+impl fmt::Display for ReplayError {
+	fn fmt (&self, f : &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ReplayError::NotAReplay => write!(f, "not a replay file"),
+			ReplayError::UnsupportedVersion(v) => write!(f, "replay version {} isn't supported, expected {}", v, VERSION),
+			ReplayError::Truncated => write!(f, "replay file is truncated"),
+			ReplayError::Invalid => write!(f, "replay file is corrupt"),
+			ReplayError::OutOfOrder => write!(f, "replay inputs aren't in tick order"),
+		}
+	}
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+	/// Little endian header, then each input as the ticks since the last one
+	/// (LEB128) and a flags byte. The inputs have to be in tick order.
+	pub fn to_bytes (&self) -> Result<Vec<u8>, ReplayError> {
+		let mut bytes = Vec::with_capacity(52 + self.inputs.len() * 2);
+		let level = &self.level;
+		
+		bytes.extend_from_slice(MAGIC);
+		bytes.push(VERSION);
+		bytes.extend_from_slice(&self.run_seed.to_le_bytes());
+		bytes.extend_from_slice(&(level.index as u32).to_le_bytes());
+		bytes.push(level.theme as u8);
+		
+		for value in [
+			level.distance,
+			level.spawner.speed,
+			level.spawner.interval,
+			level.spawner.gap_min,
+			level.spawner.gap_max,
+			level.spawner.margin,
+		] {
+			bytes.extend_from_slice(&value.to_le_bytes());
+		}
+		
+		bytes.extend_from_slice(&(self.inputs.len() as u32).to_le_bytes());
+		
+		let mut last_tick = 0;
+		
+		for input in &self.inputs {
+			let mut delta = input.tick.checked_sub(last_tick).ok_or(ReplayError::OutOfOrder)?;
+			last_tick = input.tick;
+			
+			loop {
+				let byte = (delta & 0x7f) as u8;
+				delta >>= 7;
+				
+				if delta == 0 {
+					bytes.push(byte);
+					break;
+				}
+				
+				bytes.push(byte | 0x80);
+			}
+			
+			bytes.push(input.pressed as u8 | (input.held as u8) << 1);
+		}
+		
+		Ok(bytes)
+	}
+	
+	pub fn from_bytes (bytes : &[u8]) -> Result<Self, ReplayError> {
+		let mut reader = Reader(bytes);
+		
+		if reader.take(4)? != MAGIC {
+			return Err(ReplayError::NotAReplay);
+		}
+		
+		let version = reader.u8()?;
+		if version != VERSION {
+			return Err(ReplayError::UnsupportedVersion(version));
+		}
+		
+		let run_seed = u64::from_le_bytes(reader.array()?);
+		let index = u32::from_le_bytes(reader.array()?) as usize;
+		
+		let theme = match reader.u8()? {
+			0 => LevelTheme::Grass,
+			1 => LevelTheme::Snow,
+			2 => LevelTheme::Ice,
+			_ => return Err(ReplayError::Invalid),
+		};
+		
+		let level = Level {
+			index,
+			theme,
+			distance: reader.f32()?,
+			spawner: ObstacleSpawner {
+				speed: reader.f32()?,
+				interval: reader.f32()?,
+				gap_min: reader.f32()?,
+				gap_max: reader.f32()?,
+				margin: reader.f32()?,
+			},
+		};
+		
+		let count = u32::from_le_bytes(reader.array()?);
+		let mut inputs = Vec::new();
+		let mut tick = 0u32;
+		
+		for _ in 0..count {
+			let mut delta = 0u32;
+			
+			for shift in (0..32).step_by(7) {
+				let byte = reader.u8()?;
+				
+				// The fifth byte ends the number, and only has room for its
+				// top four bits
+				if shift == 28 && byte & 0xf0 != 0 {
+					return Err(ReplayError::Invalid);
+				}
+				
+				delta |= ((byte & 0x7f) as u32) << shift;
+				
+				if byte & 0x80 == 0 { break; }
+			}
+			
+			tick = tick.checked_add(delta).ok_or(ReplayError::Invalid)?;
+			let flags = reader.u8()?;
+			
+			inputs.push(ReplayInput {
+				tick,
+				pressed: flags & 1 != 0,
+				held: flags & 2 != 0,
+			});
+		}
+		
+		if !reader.0.is_empty() {
+			return Err(ReplayError::Invalid);
+		}
+		
+		Ok(Replay { run_seed, level, inputs })
+	}
+}
+
+/// Reads values off the front of a byte slice
+struct Reader<'a> (&'a [u8]);
+
+impl<'a> Reader<'a> {
+	fn take (&mut self, count : usize) -> Result<&'a [u8], ReplayError> {
+		if self.0.len() < count {
+			return Err(ReplayError::Truncated);
+		}
+		
+		let (taken, rest) = self.0.split_at(count);
+		self.0 = rest;
+		Ok(taken)
+	}
+	
+	fn array<const N : usize> (&mut self) -> Result<[u8; N], ReplayError> {
+		Ok(self.take(N)?.try_into().unwrap())
+	}
+	
+	fn u8 (&mut self) -> Result<u8, ReplayError> {
+		Ok(self.take(1)?[0])
+	}
+	
+	fn f32 (&mut self) -> Result<f32, ReplayError> {
+		Ok(f32::from_le_bytes(self.array()?))
+	}
+}
+
+// Resources
+// =========================================================================
+
+/// The level attempt being recorded
+#[derive(Resource, Default)]
+struct Recorder {
+	replay : Option<Replay>,
+	held   : bool,
+}
+
+/// Recorded flap input driving the plane instead of the player. Insert one
+/// and run `play_back` in `FlapInputSet` to fly it.
+#[derive(Resource)]
+pub struct Playback {
+	inputs : Vec<ReplayInput>,
+	next   : usize,
+	held   : bool,
+}
+
+impl Playback {
+	/// `inputs` must be in tick order
+	pub fn new (inputs : Vec<ReplayInput>) -> Self {
+		Playback { inputs, next: 0, held: false }
+	}
+}
+
+// Systems
+// =========================================================================
+
+/// `--replay <file>` plays a saved replay, `--replay` alone plays the last run
+fn load_from_args (
+	mut commands : Commands,
+	storage : Res<Storage>,
+	mut level : ResMut<Level>,
+	mut rng : ResMut<GameRng>,
+	mut to_state : ResMut<TransitionTo>,
+) {
+	let mut args = std::env::args().skip_while(|arg| arg != "--replay");
+	if args.next().is_none() { return; }
+	
+	let bytes = match args.next() {
+		Some(path) => std::fs::read(&path).ok(),
+		None => storage.read(LAST_REPLAY),
+	};
+	
+	let Some(bytes) = bytes else {
+		error!("Couldn't read the replay");
+		return;
+	};
+	
+	let replay = match Replay::from_bytes(&bytes) {
+		Ok(replay) => replay,
+		Err(e) => {
+			error!("Couldn't play the replay: {}", e);
+			return;
+		}
+	};
+	
+	info!("Playing back level {} with {} inputs", replay.level.index, replay.inputs.len());
+	
+	*level = replay.level.clone();
+	*rng = GameRng::new(replay.run_seed);
+	to_state.0 = Some(AppState::Game);
+	
+	commands.insert_resource(Playback::new(replay.inputs));
+}
+
+fn start_recording (
+	mut recorder : ResMut<Recorder>,
+	playback : Option<Res<Playback>>,
+	attract : Option<Res<Attract>>,
+	level : Res<Level>,
+	rng : Res<GameRng>,
+) {
+	// Attract mode would overwrite the player's last run
+	if playback.is_some() || attract.is_some() { return; }
+	
+	*recorder = Recorder {
+		replay: Some(Replay {
+			run_seed: rng.run_seed(),
+			level: level.clone(),
+			inputs: Vec::new(),
+		}),
+		held: false,
+	};
+}
+
+fn record (
+	flap : Res<FlapInput>,
+	tick : Res<PhysicsTick>,
+	mut recorder : ResMut<Recorder>,
+) {
+	let Recorder { replay, held } = &mut *recorder;
+	let Some(replay) = replay else { return; };
+	
+	if flap.pressed || flap.held != *held {
+		*held = flap.held;
+		replay.inputs.push(ReplayInput {
+			tick: tick.0,
+			pressed: flap.pressed,
+			held: flap.held,
+		});
+	}
+}
+
+/// Sets the flap input from the `Playback` inputs up to this tick
+pub fn play_back (
+	mut flap : ResMut<FlapInput>,
+	tick : Res<PhysicsTick>,
+	mut playback : ResMut<Playback>,
+) {
+	let playback = &mut *playback;
+	flap.pressed = false;
+	
+	while let Some(input) = playback.inputs.get(playback.next) {
+		if input.tick > tick.0 { break; }
+		
+		flap.pressed |= input.pressed;
+		playback.held = input.held;
+		playback.next += 1;
+	}
+	
+	flap.held = playback.held;
+}
+
+fn save_recording (
+	mut recorder : ResMut<Recorder>,
+	storage : Res<Storage>,
+) {
+	let Some(replay) = recorder.replay.take() else { return; };
+	
+	match replay.to_bytes() {
+		Ok(bytes) => {
+			storage.write(LAST_REPLAY, &bytes);
+			info!("Saved replay of level {} ({} inputs)", replay.level.index, replay.inputs.len());
+		}
+		Err(e) => error!("Couldn't save the replay: {}", e),
+	}
+}
+
+/// Drops a run left unsaved, like one quit from the pause menu, so nothing
+/// records into it after the game
+fn stop_recording (
+	mut recorder : ResMut<Recorder>,
+) {
+	*recorder = Recorder::default();
+}
+
+fn finish_playback (
+	mut commands : Commands,
+) {
+	commands.remove_resource::<Playback>();
+}
+
+// Tests
+// =========================================================================
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::BASE_LEVEL;
+	
+	fn replay (inputs : Vec<ReplayInput>) -> Replay {
+		Replay { run_seed: 0xDEAD_BEEF_0123_4567, level: BASE_LEVEL, inputs }
+	}
+	
+	/// A replay with a single input, its tick written as `delta`
+	fn with_delta (delta : &[u8]) -> Vec<u8> {
+		let mut bytes = replay(Vec::new()).to_bytes().unwrap();
+		let count = bytes.len() - 4;
+		bytes[count..].copy_from_slice(&1u32.to_le_bytes());
+		bytes.extend_from_slice(delta);
+		bytes.push(1);
+		bytes
+	}
+	
+	#[test]
+	fn round_trip () {
+		let inputs = vec![
+			ReplayInput { tick: 0, pressed: true, held: true },
+			ReplayInput { tick: 12, pressed: false, held: false },
+			ReplayInput { tick: 300, pressed: true, held: false },
+			ReplayInput { tick: u32::MAX, pressed: false, held: true },
+		];
+		
+		let read = Replay::from_bytes(&replay(inputs.clone()).to_bytes().unwrap()).unwrap();
+		
+		assert_eq!(read.run_seed, 0xDEAD_BEEF_0123_4567);
+		assert_eq!(read.level.index, BASE_LEVEL.index);
+		assert_eq!(read.level.theme, BASE_LEVEL.theme);
+		assert_eq!(read.level.distance, BASE_LEVEL.distance);
+		assert_eq!(read.level.spawner.margin, BASE_LEVEL.spawner.margin);
+		assert_eq!(read.inputs, inputs);
+	}
+	
+	#[test]
+	fn rejects_out_of_order_inputs () {
+		let inputs = vec![
+			ReplayInput { tick: 300, pressed: true, held: false },
+			ReplayInput { tick: 12, pressed: true, held: false },
+		];
+		
+		assert!(matches!(replay(inputs).to_bytes(), Err(ReplayError::OutOfOrder)));
+	}
+	
+	#[test]
+	fn rejects_truncated () {
+		let bytes = replay(vec![ReplayInput { tick: 1000, pressed: true, held: true }]).to_bytes().unwrap();
+		
+		for len in 0..bytes.len() {
+			assert!(Replay::from_bytes(&bytes[..len]).is_err(), "read {} of {} bytes", len, bytes.len());
+		}
+	}
+	
+	#[test]
+	fn rejects_trailing_bytes () {
+		let mut bytes = replay(Vec::new()).to_bytes().unwrap();
+		bytes.push(0);
+		
+		assert!(matches!(Replay::from_bytes(&bytes), Err(ReplayError::Invalid)));
+	}
+	
+	#[test]
+	fn rejects_overlong_ticks () {
+		assert!(Replay::from_bytes(&with_delta(&[0xff, 0xff, 0xff, 0xff, 0x0f])).is_ok());
+		
+		// Still going after five bytes
+		assert!(matches!(
+			Replay::from_bytes(&with_delta(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x00])),
+			Err(ReplayError::Invalid),
+		));
+		
+		// More than 32 bits
+		assert!(matches!(
+			Replay::from_bytes(&with_delta(&[0xff, 0xff, 0xff, 0xff, 0x1f])),
+			Err(ReplayError::Invalid),
+		));
+	}
+}
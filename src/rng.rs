@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.insert_resource(GameRng::new(rand::random()))
+		;
+	}
+}
+
+// Structs
+// =========================================================================
+
+/// Independent sequences of random numbers, so drawing more from one never
+/// changes what another produces
+#[derive(Copy, Clone, Debug)]
+pub enum Stream {
+	Obstacles,
+	Themes,
+	Cosmetics,
+}
+
+// Resources
+// =========================================================================
+
+/// Every random number the game uses comes from here. A run is reproducible
+/// from its seed, and each level's numbers from the run seed and its index.
+#[derive(Resource)]
+pub struct GameRng {
+	run_seed  : u64,
+	obstacles : ChaCha8Rng,
+	cosmetics : ChaCha8Rng,
+}
+
+impl GameRng {
+	pub fn new (run_seed : u64) -> Self {
+		info!("Run seed {}", run_seed);
+		
+		GameRng {
+			run_seed,
+			obstacles: stream_rng(level_seed(run_seed, 0), Stream::Obstacles),
+			cosmetics: stream_rng(run_seed, Stream::Cosmetics),
+		}
+	}
+	
+	pub fn run_seed (&self) -> u64 {
+		self.run_seed
+	}
+	
+	/// Restarts the per-level streams from the start of level `index`
+	pub fn start_level (&mut self, index : usize) {
+		self.obstacles = self.level(index, Stream::Obstacles);
+	}
+	
+	/// A fresh copy of `stream` as it starts for level `index`
+	pub fn level (&self, index : usize, stream : Stream) -> ChaCha8Rng {
+		stream_rng(level_seed(self.run_seed, index), stream)
+	}
+	
+	pub fn obstacles (&mut self) -> &mut ChaCha8Rng {
+		&mut self.obstacles
+	}
+	
+	/// For randomness that only affects looks, and carries on across levels
+	pub fn cosmetics (&mut self) -> &mut ChaCha8Rng {
+		&mut self.cosmetics
+	}
+}
+
+// Helpers
+// =========================================================================
+
+/// Seed for level `index` of the run started with `run_seed`
+pub fn level_seed (run_seed : u64, index : usize) -> u64 {
+	splitmix64(run_seed ^ splitmix64(index as u64))
+}
+
+fn stream_rng (seed : u64, stream : Stream) -> ChaCha8Rng {
+	let mut rng = ChaCha8Rng::seed_from_u64(seed);
+	rng.set_stream(stream as u64);
+	rng
+}
+
+/// Scrambles neighbouring numbers into unrelated ones
+fn splitmix64 (x : u64) -> u64 {
+	let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+	z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+	z ^ (z >> 31)
+}
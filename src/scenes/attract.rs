@@ -0,0 +1,216 @@
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy_tweening::TweenCompleted;
+use rand::Rng;
+use crate::actions::{Action, ActionsSet};
+use crate::autopilot::Autopilot;
+use crate::sprite_animation::{SpriteAnimationIndices, SpriteAnimationTimer};
+use crate::{AppState, BASE_LEVEL, GAME_OVER_ANIM_COMPLETE, GameState, Level, z};
+use crate::assets::SpriteSheet;
+use crate::rng::GameRng;
+use crate::scenes::spawn_text;
+use crate::transitions::TransitionTo;
+
+/// How long the menu sits untouched before the autopilot starts flying
+const ATTRACT_DELAY : Duration = Duration::from_secs(15);
+
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.insert_resource(MenuIdle(Timer::new(ATTRACT_DELAY, TimerMode::Once)))
+			.add_system(reset_idle.in_schedule(OnEnter(AppState::Menu)))
+			.add_system(end_attract.in_schedule(OnEnter(AppState::Menu)))
+			.add_system(wait_idle.in_set(OnUpdate(AppState::Menu)))
+			
+			.add_system(
+				attract_setup
+					.run_if(resource_exists::<Attract>())
+					.in_schedule(OnEnter(AppState::Game))
+			)
+			.add_system(attract_teardown.in_schedule(OnExit(AppState::Game)))
+			.add_system(
+				attract_dead
+					.run_if(resource_exists::<Attract>())
+					.in_set(OnUpdate(GameState::Dead))
+			)
+			.add_system(
+				leave_attract
+					.run_if(resource_exists::<Attract>())
+					.in_base_set(CoreSet::PreUpdate)
+					.after(ActionsSet)
+			)
+		;
+	}
+}
+
+// Resources
+// =========================================================================
+
+/// Exists while the autopilot is showing the game off behind the title. Holds
+/// the player's level and run, which come back once the menu does.
+#[derive(Resource)]
+pub struct Attract {
+	level          : Level,
+	rng            : GameRng,
+	owns_autopilot : bool,
+}
+
+/// Counts down to attract mode while nothing is pressed on the menu
+#[derive(Resource)]
+struct MenuIdle (Timer);
+
+// Components
+// =========================================================================
+
+#[derive(Component)]
+struct AttractRoot;
+
+// Systems
+// =========================================================================
+
+// Menu
+// -------------------------------------------------------------------------
+
+fn reset_idle (
+	mut idle : ResMut<MenuIdle>,
+) {
+	idle.0.reset();
+}
+
+fn wait_idle (
+	mut commands : Commands,
+	time : Res<Time>,
+	mut idle : ResMut<MenuIdle>,
+	input : AnyInput,
+	mut level : ResMut<Level>,
+	mut rng : ResMut<GameRng>,
+	autopilot : Option<Res<Autopilot>>,
+	mut to_state : ResMut<TransitionTo>,
+) {
+	if input.any_just_pressed() {
+		idle.0.reset();
+		return;
+	}
+	
+	if to_state.0.is_some() || !idle.0.tick(time.delta()).just_finished() { return; }
+	
+	// A fresh run, so the demo never touches the player's seed or ghosts
+	let seed = rng.cosmetics().gen();
+	
+	commands.insert_resource(Attract {
+		level: std::mem::replace(&mut *level, BASE_LEVEL),
+		rng: std::mem::replace(&mut *rng, GameRng::new(seed)),
+		owns_autopilot: autopilot.is_none(),
+	});
+	
+	if autopilot.is_none() {
+		commands.insert_resource(Autopilot::default());
+	}
+	
+	to_state.0 = Some(AppState::Game);
+}
+
+/// Puts back what attract mode borrowed
+fn end_attract (
+	world : &mut World,
+) {
+	let Some(attract) = world.remove_resource::<Attract>() else { return; };
+	
+	world.insert_resource(attract.level);
+	world.insert_resource(attract.rng);
+	
+	if attract.owns_autopilot {
+		world.remove_resource::<Autopilot>();
+	}
+}
+
+// Game
+// -------------------------------------------------------------------------
+
+fn attract_setup (
+	mut commands : Commands,
+	sprite_sheet : Res<SpriteSheet>,
+	atlases : Res<Assets<TextureAtlas>>,
+) {
+	commands.spawn((
+		AttractRoot,
+		SpatialBundle::from_transform(Transform::from_xyz(0., 0., z::OVERLAY)),
+	)).with_children(|commands| {
+		commands.spawn(SpatialBundle::from_transform(
+			Transform::from_xyz(0., 20., 0.)
+		)).with_children(|commands| {
+			spawn_text(commands, &sprite_sheet, &atlases, "Tappy Plane", 1.);
+		});
+		
+		commands.spawn((
+			SpriteSheetBundle {
+				texture_atlas: sprite_sheet.handle.clone(),
+				sprite: sprite_sheet.get("tap"),
+				transform: Transform::from_xyz(0., -80., 0.),
+				..default()
+			},
+			SpriteAnimationIndices::new(vec![
+				sprite_sheet.get("tap").index,
+				sprite_sheet.get("tapTick").index,
+			]),
+			SpriteAnimationTimer(Timer::from_seconds(0.5, TimerMode::Repeating)),
+		));
+	});
+}
+
+fn attract_teardown (
+	mut commands : Commands,
+	query : Query<Entity, With<AttractRoot>>,
+) {
+	for entity in &query {
+		commands.entity(entity).despawn_recursive();
+	}
+}
+
+/// Back to the menu once the bot has crashed, rather than waiting for a tap
+fn attract_dead (
+	mut reader : EventReader<TweenCompleted>,
+	mut to_state : ResMut<TransitionTo>,
+) {
+	if reader.iter().any(|event| event.user_data == GAME_OVER_ANIM_COMPLETE) {
+		to_state.0 = Some(AppState::Menu);
+	}
+}
+
+/// Any input at all goes back to the menu, and is swallowed so the game
+/// doesn't act on it too
+fn leave_attract (
+	input : AnyInput,
+	mut actions : ResMut<Input<Action>>,
+	mut to_state : ResMut<TransitionTo>,
+) {
+	if !input.any_just_pressed() { return; }
+	
+	actions.reset_all();
+	
+	// Also cancels a wipe still on its way into the game
+	to_state.0 = Some(AppState::Menu);
+}
+
+// Helpers
+// =========================================================================
+
+/// Every device the player could touch, whatever it's bound to
+#[derive(bevy::ecs::system::SystemParam)]
+struct AnyInput<'w> {
+	keys    : Res<'w, Input<KeyCode>>,
+	mouse   : Res<'w, Input<MouseButton>>,
+	touch   : Res<'w, Touches>,
+	buttons : Res<'w, Input<GamepadButton>>,
+}
+
+impl AnyInput<'_> {
+	fn any_just_pressed (&self) -> bool {
+		self.keys.get_just_pressed().next().is_some()
+			|| self.mouse.get_just_pressed().next().is_some()
+			|| self.touch.any_just_pressed()
+			|| self.buttons.get_just_pressed().next().is_some()
+	}
+}
@@ -3,11 +3,13 @@ use bevy::prelude::*;
 use bevy::sprite::MaterialMesh2dBundle;
 use bevy_tweening::{Animator, Delay, EaseFunction, Tracks, Tween, TweenCompleted};
 use bevy_tweening::lens::{TransformPositionLens, TransformScaleLens};
+use crate::actions::Action;
 use crate::sprite_animation::{SpriteAnimationIndices, SpriteAnimationTimer};
-use crate::{AppState, BASE_LEVEL, DIST_PER_SECOND, DistanceTravelled, GAME_IN_ANIM_COMPLETE, GAME_OUT_ANIM_COMPLETE, GAME_OVER_ANIM_COMPLETE, GameState, Level, LevelTheme, SCREEN_HEIGHT, SCREEN_WIDTH, z};
+use crate::{AppState, BASE_LEVEL, DistanceTravelled, GAME_IN_ANIM_COMPLETE, GAME_OUT_ANIM_COMPLETE, GAME_OVER_ANIM_COMPLETE, GameState, Level, LevelTheme, SCREEN_HEIGHT, SCREEN_WIDTH, z};
 use crate::assets::SpriteSheet;
 use crate::obstacle::SpawnTimer;
-use crate::physics::{AABBCollider, Velocity};
+use crate::level::{GROUND_POSITION, PLANE_OFFSET, PLANE_START, ceiling, ground_collider, ground_speed, plane_body};
+use crate::rng::GameRng;
 use crate::shaders::ScrollMaterial;
 use crate::transitions::TransitionTo;
 
@@ -16,9 +18,6 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
 	fn build(&self, app: &mut App) {
 		app
-			.insert_resource(GroundSpeed(300.))
-			.insert_resource(DeathSpeed(0.))
-			
 			.add_system(setup_game.in_schedule(OnEnter(AppState::Game)))
 			.add_system(teardown_game.in_schedule(OnExit(AppState::Game)))
 			
@@ -27,23 +26,15 @@ impl Plugin for GamePlugin {
 			.add_system(animate_out.in_schedule(OnEnter(GameState::Exit)))
 			
 			.add_system(early_start.in_set(OnUpdate(GameState::Enter)))
-			.add_system(travel.in_set(OnUpdate(GameState::Play)))
-		
+			.add_system(snap_plane.in_schedule(OnEnter(GameState::Play)))
+			.add_system(update_progress.in_set(OnUpdate(GameState::Play)))
+			
 			.add_system(dead_enter.in_schedule(OnEnter(GameState::Dead)))
 			.add_system(dead_loop.in_set(OnUpdate(GameState::Dead)))
 		;
 	}
 }
 
-// Resources
-// =========================================================================
-
-#[derive(Resource)]
-pub struct GroundSpeed (pub f32);
-
-#[derive(Resource)]
-pub struct DeathSpeed (pub f32);
-
 // Components
 // =========================================================================
 
@@ -51,7 +42,7 @@ pub struct DeathSpeed (pub f32);
 pub struct GameRoot;
 
 #[derive(Component)]
-struct PlaneRoot;
+pub struct PlaneRoot;
 
 #[derive(Component)]
 struct Plane;
@@ -80,23 +71,8 @@ fn setup_game(
 	mut mesh_assets : ResMut<Assets<Mesh>>,
 	mut scroll_material_assets : ResMut<Assets<ScrollMaterial>>,
 	mut state : ResMut<NextState<GameState>>,
-	mut ground_speed : ResMut<GroundSpeed>,
 	level : Res<Level>,
-	mut timer : ResMut<SpawnTimer>,
-	mut death_speed : ResMut<DeathSpeed>,
-	mut distance_travelled : ResMut<DistanceTravelled>,
 ) {
-	// Reset counters
-	death_speed.0 = 0.;
-	distance_travelled.0 = 0.;
-	
-	// Setup timer
-	timer.0.set_duration(Duration::from_secs_f32(level.spawner.interval));
-	
-	// Ground speed
-	let computed_ground_speed = 300. + level.spawner.speed;
-	ground_speed.0 = computed_ground_speed;
-	
 	let theme = level.theme;
 	
 	commands.spawn((
@@ -117,6 +93,7 @@ fn setup_game(
 			mesh: mesh_assets.add(Mesh::from(shape::Quad::new(Vec2::new(SCREEN_WIDTH, top_slice)))).into(),
 			material: scroll_material_assets.add(ScrollMaterial {
 				scroll_speed: 0.05,
+				offset: 0.,
 				rect: ScrollMaterial::rect(0., 355., 800. - 0.4, top_slice),
 				texture: sprite_sheet.texture_handle.clone(),
 			}),
@@ -130,6 +107,7 @@ fn setup_game(
 			mesh: mesh_assets.add(Mesh::from(shape::Quad::new(Vec2::new(SCREEN_WIDTH, SCREEN_HEIGHT - top_slice)))).into(),
 			material: scroll_material_assets.add(ScrollMaterial {
 				scroll_speed: 0.1,
+				offset: 0.,
 				rect: ScrollMaterial::rect(0., 355. + top_slice, 800. - 0.4, 480. - top_slice),
 				texture: sprite_sheet.texture_handle.clone(),
 			}),
@@ -140,11 +118,7 @@ fn setup_game(
 		// Ceiling Collider
 		// -------------------------------------------------------------------------
 		
-		commands.spawn((
-			Transform::from_xyz(0., SCREEN_HEIGHT * 0.5 + 15., 0.),
-			GlobalTransform::default(),
-			AABBCollider(Vec2::new(SCREEN_WIDTH, 30.), None),
-		));
+		commands.spawn(ceiling());
 		
 		// Ground
 		// -------------------------------------------------------------------------
@@ -159,14 +133,15 @@ fn setup_game(
 			MaterialMesh2dBundle {
 				mesh: mesh_assets.add(Mesh::from(shape::Quad::new(Vec2::new(SCREEN_WIDTH, 71.)))).into(),
 				material: scroll_material_assets.add(ScrollMaterial {
-					scroll_speed: computed_ground_speed * 0.001,
+					scroll_speed: ground_speed(&level) * 0.001,
+					offset: 0.,
 					rect: ScrollMaterial::rect(0., ground_y, 808. - 0.4, 71.),
 					texture: sprite_sheet.texture_handle.clone(),
 				}),
-				transform: Transform::from_xyz(0., (SCREEN_HEIGHT - 71.) / 2. * -1., z::GROUND),
+				transform: Transform::from_translation(GROUND_POSITION),
 				..default()
 			},
-			AABBCollider(Vec2::new(SCREEN_WIDTH, 30.), Some(Vec2::new(0., -10.))),
+			ground_collider(),
 		));
 		
 		// Plane
@@ -174,7 +149,7 @@ fn setup_game(
 		
 		commands.spawn((
 			PlaneRoot,
-			Transform::from_xyz(SCREEN_WIDTH * -0.2, 0., z::PLANE),
+			Transform::from_translation(PLANE_START),
 			GlobalTransform::default(),
 			Visibility::default(),
 			ComputedVisibility::default(),
@@ -184,7 +159,7 @@ fn setup_game(
 				SpriteSheetBundle {
 					texture_atlas: sprite_sheet.handle.clone(),
 					sprite: sprite_sheet.get("planeBlue1"),
-					transform: Transform::from_xyz(88. * -0.5, 73. * 0.5, 0.),
+					transform: Transform::from_translation(PLANE_OFFSET),
 					..default()
 				},
 				SpriteAnimationIndices::new(vec![
@@ -193,11 +168,7 @@ fn setup_game(
 					sprite_sheet.get("planeBlue3").index,
 				]),
 				SpriteAnimationTimer(Timer::from_seconds(0.04, TimerMode::Repeating)),
-				Velocity::default(),
-				AABBCollider(
-					Vec2::new(80., 73.) * 0.6,
-					Some(Vec2::new(10., 0.)),
-				),
+				plane_body(),
 			));
 		});
 		
@@ -291,14 +262,25 @@ fn setup_game(
 
 fn early_start (
 	mut state : ResMut<NextState<GameState>>,
-	mouse : Res<Input<MouseButton>>,
-	touch : Res<Touches>,
+	actions : Res<Input<Action>>,
 ) {
-	if mouse.just_pressed(MouseButton::Left) || touch.any_just_pressed() {
+	if actions.just_pressed(Action::Flap) {
 		state.set(GameState::Play);
 	}
 }
 
+/// Puts the plane where the intro leaves it, even if the intro was cut short,
+/// so every run starts from the same place
+fn snap_plane (
+	mut commands : Commands,
+	mut query : Query<(Entity, &mut Transform), With<PlaneRoot>>,
+) {
+	for (entity, mut transform) in &mut query {
+		commands.entity(entity).remove::<Animator<Transform>>();
+		transform.translation = PLANE_START;
+	}
+}
+
 // Animations
 // -------------------------------------------------------------------------
 
@@ -314,7 +296,7 @@ fn animate_in (
 			Duration::from_secs(2),
 			TransformPositionLens {
 				start: Vec3::new(SCREEN_WIDTH * -0.8, 200., z::PLANE),
-				end: Vec3::new(SCREEN_WIDTH * -0.2, 0., z::PLANE),
+				end: PLANE_START,
 			},
 		).with_completed_event(GAME_IN_ANIM_COMPLETE);
 		
@@ -353,17 +335,14 @@ fn handle_anim_event (
 	mut state : ResMut<NextState<GameState>>,
 	mut to_state : ResMut<TransitionTo>,
 	mut level : ResMut<Level>,
+	rng : Res<GameRng>,
 ) {
 	for event in reader.iter() {
 		match event.user_data {
 			GAME_IN_ANIM_COMPLETE => { state.set(GameState::Play) }
 			GAME_OUT_ANIM_COMPLETE => {
 				to_state.0 = Some(AppState::Menu);
-				level.index += 1;
-				level.theme = rand::random();
-				level.distance += 100.;
-				level.spawner.speed += 5.;
-				level.spawner.interval -= 0.1;
+				*level = level.next(&rng);
 			}
 			GAME_OVER_ANIM_COMPLETE => { /* Handled in dead_loop */ }
 			_ => {}
@@ -420,25 +399,18 @@ fn animate_out (
 // Travel
 // -------------------------------------------------------------------------
 
-fn travel (
-	mut distance_travelled : ResMut<DistanceTravelled>,
-	time : Res<Time>,
+fn update_progress (
+	distance_travelled : Res<DistanceTravelled>,
 	level : Res<Level>,
-	mut state : ResMut<NextState<GameState>>,
 	mut bar : Query<&mut Transform, With<ProgressBar>>,
 	mut plane : Query<&mut Transform, (With<ProgressPlane>, Without<ProgressBar>)>,
 ) {
 	let mut bar = bar.single_mut();
 	let mut plane = plane.single_mut();
-	distance_travelled.0 += DIST_PER_SECOND * time.delta_seconds();
 	
 	bar.scale.x = distance_travelled.0 / level.distance;
 	bar.translation.x = ((SCREEN_WIDTH - 80.) * -0.5) * (1. - bar.scale.x);
 	plane.translation.x = (SCREEN_WIDTH * -0.5 + 45.) + (SCREEN_WIDTH - 80.) * bar.scale.x;
-	
-	if distance_travelled.0 >= level.distance {
-		state.set(GameState::Exit);
-	}
 }
 
 // Teardown
@@ -516,28 +488,23 @@ fn dead_enter (
 	});
 }
 
+/// Back to the menu once the game over has shown. The run seed is only
+/// rerolled when the game launches, so every retry until then flies the same
+/// course and can race its ghost.
 fn dead_loop (
-	mut query : Query<&mut Transform, With<Plane>>,
-	death_speed : Res<DeathSpeed>,
-	time : Res<Time>,
-	mouse : Res<Input<MouseButton>>,
-	touch : Res<Touches>,
+	actions : Res<Input<Action>>,
 	mut can_restart : Local<bool>,
 	mut reader : EventReader<TweenCompleted>,
 	mut to_state : ResMut<TransitionTo>,
 	mut level : ResMut<Level>,
 ) {
-	if let Ok(mut transform) = query.get_single_mut() {
-		transform.translation.x -= death_speed.0 * time.delta_seconds();
-	}
-	
 	for event in reader.iter() {
 		if event.user_data == GAME_OVER_ANIM_COMPLETE {
 			*can_restart = true;
 		}
 	}
 	
-	if *can_restart && (mouse.just_pressed(MouseButton::Left) || touch.any_just_pressed()) {
+	if *can_restart && actions.any_just_pressed([Action::Confirm, Action::Back]) {
 		to_state.0 = Some(AppState::Menu);
 		*level = BASE_LEVEL;
 	}
@@ -1,13 +1,21 @@
 use std::time::Duration;
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use bevy_tweening::{Animator, Delay, EaseFunction, Tween};
 use bevy_tweening::lens::TransformPositionLens;
 use rand::Rng;
+use crate::actions::{Action, ActionBindings, pointer_press};
 use crate::sprite_animation::{SpriteAnimationIndices, SpriteAnimationTimer};
-use crate::{AppState, SCREEN_HEIGHT};
+use crate::{AppState, SCREEN_HEIGHT, SCREEN_WIDTH};
 use crate::assets::SpriteSheet;
+use crate::rng::GameRng;
+use crate::scenes::spawn_text;
 use crate::transitions::TransitionTo;
 
+/// Where the settings button sits, and its size
+const SETTINGS_POSITION : Vec2 = Vec2::new(SCREEN_WIDTH * 0.5 - 80., SCREEN_HEIGHT * 0.5 - 50.);
+const SETTINGS_SIZE : Vec2 = Vec2::new(136., 80.);
+
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
@@ -32,6 +40,8 @@ struct MenuRoot;
 fn menu_setup (
 	mut commands : Commands,
 	sprite_sheet : Res<SpriteSheet>,
+	atlases : Res<Assets<TextureAtlas>>,
+	mut game_rng : ResMut<GameRng>,
 	
 	// mut mesh_assets : ResMut<Assets<Mesh>>,
 	// mut slice_material_assets : ResMut<Assets<SliceMaterial>>,
@@ -57,7 +67,7 @@ fn menu_setup (
 		// });
 		
 		
-		let mut rng = rand::thread_rng();
+		let rng = game_rng.cosmetics();
 		
 		let mut spawn = |o : f32, l : char| {
 			debug_assert!(l.is_uppercase(), "Char must be upper case!");
@@ -125,17 +135,43 @@ fn menu_setup (
 				},
 			))),
 		));
+		
+		commands.spawn(SpriteSheetBundle {
+			texture_atlas: sprite_sheet.handle.clone(),
+			sprite: sprite_sheet.get("buttonSmall"),
+			transform: Transform::from_translation(SETTINGS_POSITION.extend(1.)),
+			..default()
+		}).with_children(|commands| {
+			commands.spawn(SpatialBundle::from_transform(
+				Transform::from_xyz(0., 4., 1.)
+			)).with_children(|commands| {
+				spawn_text(commands, &sprite_sheet, &atlases, "Keys", 0.35);
+			});
+		});
 	});
 }
 
 fn menu_loop (
+	actions : Res<Input<Action>>,
+	bindings : Res<ActionBindings>,
+	windows : Query<&Window, With<PrimaryWindow>>,
 	mouse : Res<Input<MouseButton>>,
 	touch : Res<Touches>,
 	mut to_state : ResMut<TransitionTo>,
 ) {
-	if mouse.just_pressed(MouseButton::Left) || touch.any_just_pressed() {
-		to_state.0 = Some(AppState::Game);
+	// Pause (Escape or Start) opens the settings for players without a pointer
+	if actions.just_pressed(Action::Pause) {
+		to_state.0 = Some(AppState::Settings);
+		return;
 	}
+	
+	if !actions.just_pressed(Action::Confirm) { return; }
+	
+	let on_settings = windows.get_single().ok()
+		.and_then(|window| pointer_press(window, &bindings, Action::Confirm, &mouse, &touch))
+		.is_some_and(|p| (p - SETTINGS_POSITION).abs().cmple(SETTINGS_SIZE * 0.5).all());
+	
+	to_state.0 = Some(if on_settings { AppState::Settings } else { AppState::Game });
 }
 
 fn menu_teardown (
@@ -1,11 +1,17 @@
-use bevy::app::App;
-use bevy::prelude::Plugin;
-use crate::scenes::game::GamePlugin;
+use bevy::prelude::*;
+use crate::assets::SpriteSheet;
+use crate::scenes::attract::AttractPlugin;
 use crate::scenes::menu::MenuPlugin;
+use crate::scenes::pause::PausePlugin;
+use crate::scenes::settings::SettingsPlugin;
 
+mod attract;
 mod menu;
 mod game;
+mod pause;
+mod settings;
 
+pub use attract::Attract;
 pub use game::*;
 
 pub struct ScenesPlugin;
@@ -14,7 +20,62 @@ impl Plugin for ScenesPlugin {
 	fn build(&self, app: &mut App) {
 		app
 			.add_plugin(MenuPlugin)
+			.add_plugin(AttractPlugin)
 			.add_plugin(GamePlugin)
+			.add_plugin(PausePlugin)
+			.add_plugin(SettingsPlugin)
 		;
 	}
 }
+
+// Helpers
+// =========================================================================
+
+/// Gap between letters, before scaling
+const LETTER_SPACING : f32 = 4.;
+
+/// Width of a space, before scaling
+const SPACE_WIDTH : f32 = 30.;
+
+/// Spawns `text` as letter and number sprites, centred on the parent. Anything
+/// without a sprite is drawn as a space.
+fn spawn_text (
+	commands : &mut ChildBuilder,
+	sprite_sheet : &SpriteSheet,
+	atlases : &Assets<TextureAtlas>,
+	text : &str,
+	scale : f32,
+) {
+	let Some(atlas) = atlases.get(&sprite_sheet.handle) else { return; };
+	
+	let glyphs : Vec<_> = text.to_uppercase().chars().map(|c| {
+		let name = if c.is_ascii_uppercase() {
+			format!("letter{}", c)
+		} else if c.is_ascii_digit() {
+			format!("number{}", c)
+		} else {
+			return (None, SPACE_WIDTH);
+		};
+		
+		let sprite = sprite_sheet.get(&name);
+		let width = atlas.textures[sprite.index].width();
+		(Some(sprite), width)
+	}).collect();
+	
+	let total = glyphs.iter().map(|(_, w)| w + LETTER_SPACING).sum::<f32>() - LETTER_SPACING;
+	let mut x = total * -0.5;
+	
+	for (sprite, width) in glyphs {
+		if let Some(sprite) = sprite {
+			commands.spawn(SpriteSheetBundle {
+				texture_atlas: sprite_sheet.handle.clone(),
+				sprite,
+				transform: Transform::from_xyz((x + width * 0.5) * scale, 0., 0.)
+					.with_scale(Vec3::splat(scale)),
+				..default()
+			});
+		}
+		
+		x += width + LETTER_SPACING;
+	}
+}
@@ -0,0 +1,185 @@
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+use bevy_tweening::{Animator, AnimatorState};
+use crate::actions::Action;
+use crate::{AppState, GameState, SCREEN_HEIGHT, SCREEN_WIDTH, z};
+use crate::assets::SpriteSheet;
+use crate::scenes::{Attract, spawn_text};
+use crate::transitions::TransitionTo;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+	fn build(&self, app: &mut App) {
+		app
+			// Before state transitions, so a long frame after coming back to
+			// the game is spent paused instead of simulated
+			.add_system(
+				auto_pause
+					.in_base_set(CoreSet::PreUpdate)
+					.run_if(in_state(AppState::Game))
+					.run_if(in_state(GameState::Play))
+					.run_if(not(resource_exists::<Attract>()))
+			)
+			.add_system(pause.in_set(OnUpdate(AppState::Game)).in_set(OnUpdate(GameState::Play)))
+			.add_system(pause_loop.in_set(OnUpdate(GameState::Paused)))
+			.add_systems((freeze_tweens, pause_setup).in_schedule(OnEnter(GameState::Paused)))
+			.add_systems((thaw_tweens, pause_teardown).in_schedule(OnExit(GameState::Paused)))
+		;
+		
+		#[cfg(target_arch = "wasm32")]
+		app.add_startup_system(watch_visibility);
+	}
+}
+
+// Components
+// =========================================================================
+
+#[derive(Component)]
+struct PauseOverlay;
+
+/// A tween that was playing when the game paused
+#[derive(Component)]
+struct Frozen;
+
+// Page visibility
+// =========================================================================
+
+/// Set when the browser tab is hidden, cleared once the game has paused
+#[cfg(target_arch = "wasm32")]
+static PAGE_HIDDEN : std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Browsers stop drawing hidden tabs without the window losing focus, so
+/// listen for the page being hidden instead
+#[cfg(target_arch = "wasm32")]
+fn watch_visibility () {
+	use std::sync::atomic::Ordering;
+	use wasm_bindgen::closure::Closure;
+	use wasm_bindgen::JsCast;
+	
+	let Some(document) = web_sys::window().and_then(|w| w.document()) else { return; };
+	
+	let listener = Closure::<dyn Fn()>::new(|| {
+		let hidden = web_sys::window()
+			.and_then(|w| w.document())
+			.is_some_and(|d| d.hidden());
+		
+		if hidden {
+			PAGE_HIDDEN.store(true, Ordering::Relaxed);
+		}
+	});
+	
+	let _ = document.add_event_listener_with_callback(
+		"visibilitychange",
+		listener.as_ref().unchecked_ref(),
+	);
+	
+	// The listener lives as long as the page
+	listener.forget();
+}
+
+// Systems
+// =========================================================================
+
+fn auto_pause (
+	mut reader : EventReader<WindowFocused>,
+	mut state : ResMut<NextState<GameState>>,
+) {
+	#[allow(unused_mut)]
+	let mut lost_focus = reader.iter().any(|event| !event.focused);
+	
+	#[cfg(target_arch = "wasm32")]
+	{
+		lost_focus |= PAGE_HIDDEN.swap(false, std::sync::atomic::Ordering::Relaxed);
+	}
+	
+	if lost_focus {
+		state.set(GameState::Paused);
+	}
+}
+
+fn pause (
+	actions : Res<Input<Action>>,
+	mut state : ResMut<NextState<GameState>>,
+) {
+	if actions.just_pressed(Action::Pause) {
+		state.set(GameState::Paused);
+	}
+}
+
+fn pause_loop (
+	actions : Res<Input<Action>>,
+	mut state : ResMut<NextState<GameState>>,
+	mut to_state : ResMut<TransitionTo>,
+) {
+	if to_state.0.is_some() { return; }
+	
+	// Escape is both pause and back, so resuming wins
+	if actions.any_just_pressed([Action::Pause, Action::Confirm]) {
+		state.set(GameState::Play);
+	} else if actions.just_pressed(Action::Back) {
+		to_state.0 = Some(AppState::Menu);
+	}
+}
+
+fn freeze_tweens (
+	mut commands : Commands,
+	mut query : Query<(Entity, &mut Animator<Transform>)>,
+) {
+	for (entity, mut animator) in &mut query {
+		if animator.state == AnimatorState::Playing {
+			animator.state = AnimatorState::Paused;
+			commands.entity(entity).insert(Frozen);
+		}
+	}
+}
+
+fn thaw_tweens (
+	mut commands : Commands,
+	mut query : Query<(Entity, &mut Animator<Transform>), With<Frozen>>,
+) {
+	for (entity, mut animator) in &mut query {
+		animator.state = AnimatorState::Playing;
+		commands.entity(entity).remove::<Frozen>();
+	}
+}
+
+fn pause_setup (
+	mut commands : Commands,
+	sprite_sheet : Res<SpriteSheet>,
+	atlases : Res<Assets<TextureAtlas>>,
+) {
+	commands.spawn((
+		PauseOverlay,
+		SpriteBundle {
+			sprite: Sprite {
+				color: Color::rgba(0., 0., 0., 0.4),
+				custom_size: Some(Vec2::new(SCREEN_WIDTH, SCREEN_HEIGHT)),
+				..default()
+			},
+			transform: Transform::from_xyz(0., 0., z::OVERLAY),
+			..default()
+		},
+	)).with_children(|commands| {
+		commands.spawn(SpatialBundle::from_transform(
+			Transform::from_xyz(0., 20., 1.)
+		)).with_children(|commands| {
+			spawn_text(commands, &sprite_sheet, &atlases, "Paused", 0.8);
+		});
+		
+		commands.spawn(SpatialBundle::from_transform(
+			Transform::from_xyz(0., -50., 1.)
+		)).with_children(|commands| {
+			spawn_text(commands, &sprite_sheet, &atlases, "Back to quit", 0.3);
+		});
+	});
+}
+
+fn pause_teardown (
+	mut commands : Commands,
+	query : Query<Entity, With<PauseOverlay>>,
+) {
+	for entity in &query {
+		commands.entity(entity).despawn_recursive();
+	}
+}
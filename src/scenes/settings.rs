@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use crate::actions::{Action, ActionBindings, Binding, pointer_press, REBINDABLE};
+use crate::{AppState, SCREEN_HEIGHT};
+use crate::assets::SpriteSheet;
+use crate::scenes::spawn_text;
+use crate::storage::Storage;
+use crate::transitions::TransitionTo;
+
+/// Where the cancel button sits, and its size. It's pressed with the
+/// pointer, as every key and button press is taken as a new binding.
+const CANCEL_POSITION : Vec2 = Vec2::new(0., SCREEN_HEIGHT * -0.35);
+const CANCEL_SIZE : Vec2 = Vec2::new(136., 80.);
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.add_system(settings_setup.in_schedule(OnEnter(AppState::Settings)))
+			.add_systems(
+				(
+					settings_loop,
+					// Conditions are checked even outside the state, when
+					// there's no `Rebinding` to look at
+					show_step.run_if(resource_exists::<Rebinding>().and_then(resource_changed::<Rebinding>())),
+				).chain()
+				 .in_set(OnUpdate(AppState::Settings))
+			)
+			.add_system(settings_teardown.in_schedule(OnExit(AppState::Settings)))
+		;
+	}
+}
+
+// Resources
+// =========================================================================
+
+/// Bindings being edited, applied once every action has been rebound
+#[derive(Resource)]
+struct Rebinding {
+	bindings : ActionBindings,
+	step     : usize,
+}
+
+// Components
+// =========================================================================
+
+#[derive(Component)]
+struct SettingsRoot;
+
+/// Text describing the current step, respawned whenever it moves on
+#[derive(Component)]
+struct StepText;
+
+// Systems
+// =========================================================================
+
+fn settings_setup (
+	mut commands : Commands,
+	sprite_sheet : Res<SpriteSheet>,
+	atlases : Res<Assets<TextureAtlas>>,
+	bindings : Res<ActionBindings>,
+) {
+	commands.spawn((
+		SettingsRoot,
+		SpatialBundle::default(),
+	)).with_children(|commands| {
+		commands.spawn(SpatialBundle::from_transform(
+			Transform::from_xyz(0., SCREEN_HEIGHT * 0.3, 0.)
+		)).with_children(|commands| {
+			spawn_text(commands, &sprite_sheet, &atlases, "Controls", 0.8);
+		});
+		
+		commands.spawn(SpriteSheetBundle {
+			texture_atlas: sprite_sheet.handle.clone(),
+			sprite: sprite_sheet.get("buttonSmall"),
+			transform: Transform::from_translation(CANCEL_POSITION.extend(1.)),
+			..default()
+		}).with_children(|commands| {
+			commands.spawn(SpatialBundle::from_transform(
+				Transform::from_xyz(0., 4., 1.)
+			)).with_children(|commands| {
+				spawn_text(commands, &sprite_sheet, &atlases, "Cancel", 0.35);
+			});
+		});
+	});
+	
+	commands.insert_resource(Rebinding {
+		bindings: bindings.clone(),
+		step: 0,
+	});
+}
+
+fn settings_loop (
+	windows : Query<&Window, With<PrimaryWindow>>,
+	keys : Res<Input<KeyCode>>,
+	mouse : Res<Input<MouseButton>>,
+	touch : Res<Touches>,
+	buttons : Res<Input<GamepadButton>>,
+	mut rebinding : ResMut<Rebinding>,
+	mut bindings : ResMut<ActionBindings>,
+	storage : Res<Storage>,
+	mut to_state : ResMut<TransitionTo>,
+) {
+	if to_state.0.is_some() || rebinding.step >= REBINDABLE.len() { return; }
+	
+	// Clicked with the bindings in use, not the ones being edited
+	let on_cancel = windows.get_single().ok()
+		.and_then(|window| pointer_press(window, &bindings, Action::Confirm, &mouse, &touch))
+		.is_some_and(|p| (p - CANCEL_POSITION).abs().cmple(CANCEL_SIZE * 0.5).all());
+	
+	if on_cancel {
+		to_state.0 = Some(AppState::Menu);
+		return;
+	}
+	
+	let pressed = keys.get_just_pressed().next().map(|key| Binding::Key(*key))
+		.or_else(|| mouse.get_just_pressed().next().map(|button| Binding::Mouse(*button)))
+		.or_else(|| buttons.get_just_pressed().next().map(|button| Binding::Gamepad(button.button_type)));
+	
+	let Some(binding) = pressed else { return; };
+	
+	let action = REBINDABLE[rebinding.step];
+	rebinding.bindings.rebind(action, binding);
+	rebinding.step += 1;
+	
+	if rebinding.step == REBINDABLE.len() {
+		*bindings = rebinding.bindings.clone();
+		bindings.save(&storage);
+		to_state.0 = Some(AppState::Menu);
+	}
+}
+
+fn settings_teardown (
+	mut commands : Commands,
+	query : Query<Entity, Or<(With<SettingsRoot>, With<StepText>)>>,
+) {
+	for entity in &query {
+		commands.entity(entity).despawn_recursive();
+	}
+	
+	commands.remove_resource::<Rebinding>();
+}
+
+/// Shows which action is being rebound and what it is currently bound to
+fn show_step (
+	mut commands : Commands,
+	sprite_sheet : Res<SpriteSheet>,
+	atlases : Res<Assets<TextureAtlas>>,
+	rebinding : Res<Rebinding>,
+	query : Query<Entity, With<StepText>>,
+) {
+	for entity in &query {
+		commands.entity(entity).despawn_recursive();
+	}
+	
+	if rebinding.step >= REBINDABLE.len() { return; }
+	
+	let action = REBINDABLE[rebinding.step];
+	
+	let current = rebinding.bindings.for_action(action)
+		.filter(|binding| *binding != Binding::Touch)
+		.map(|binding| binding.name())
+		.collect::<Vec<_>>()
+		.join("  ");
+	
+	commands.spawn((
+		StepText,
+		SpatialBundle::default(),
+	)).with_children(|commands| {
+		commands.spawn(SpatialBundle::from_transform(
+			Transform::from_xyz(0., 50., 0.)
+		)).with_children(|commands| {
+			spawn_text(commands, &sprite_sheet, &atlases, &format!("Press {:?}", action), 0.6);
+		});
+		
+		commands.spawn(SpatialBundle::from_transform(
+			Transform::from_xyz(0., -30., 0.)
+		)).with_children(|commands| {
+			spawn_text(commands, &sprite_sheet, &atlases, &current, 0.3);
+		});
+	});
+}
@@ -3,6 +3,8 @@ mod scroll_material;
 
 use bevy::prelude::*;
 use bevy::sprite::Material2dPlugin;
+use crate::GameState;
+use crate::shaders::scroll_material::scroll;
 pub use scroll_material::ScrollMaterial;
 // pub use slice_material::SliceMaterial;
 
@@ -13,6 +15,7 @@ impl Plugin for ShadersPlugin {
 		app
 			.add_plugin(Material2dPlugin::<ScrollMaterial>::default())
 			// .add_plugin(Material2dPlugin::<SliceMaterial>::default())
+			.add_system(scroll.run_if(not(in_state(GameState::Paused))))
 		;
 	}
 }
@@ -8,6 +8,10 @@ use bevy::sprite::Material2d;
 pub struct ScrollMaterial {
 	#[uniform(0)]
 	pub scroll_speed : f32,
+	/// How far the texture has scrolled, advanced by `scroll` so it stops
+	/// while the game is paused
+	#[uniform(0)]
+	pub offset : f32,
 	#[uniform(0)]
 	pub rect : Vec4,
 	#[texture(1)]
@@ -31,3 +35,15 @@ impl Material2d for ScrollMaterial {
 		"shaders/scroll.wgsl".into()
 	}
 }
+
+// Systems
+// =========================================================================
+
+pub fn scroll (
+	mut materials : ResMut<Assets<ScrollMaterial>>,
+	time : Res<Time>,
+) {
+	for (_, material) in materials.iter_mut() {
+		material.offset = (material.offset + material.scroll_speed * time.delta_seconds()).fract();
+	}
+}
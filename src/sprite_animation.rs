@@ -1,11 +1,12 @@
 use bevy::prelude::*;
+use crate::GameState;
 
 pub struct SpriteAnimationPlugin;
 
 impl Plugin for SpriteAnimationPlugin {
 	fn build(&self, app: &mut App) {
 		app
-			.add_system(animate_sprite)
+			.add_system(animate_sprite.run_if(not(in_state(GameState::Paused))))
 		;
 	}
 }
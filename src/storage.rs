@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub struct StoragePlugin;
+
+impl Plugin for StoragePlugin {
+	fn build(&self, app: &mut App) {
+		app
+			.insert_resource(Storage::default())
+		;
+	}
+}
+
+// Backends
+// =========================================================================
+
+/// Somewhere to keep small files between runs
+pub trait StorageBackend : Send + Sync {
+	fn read (&self, name : &str) -> Option<Vec<u8>>;
+	fn write (&self, name : &str, data : &[u8]);
+}
+
+/// Stores files in the user's config directory
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+	dir : std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for FileStorage {
+	fn default () -> Self {
+		let base = std::env::var_os("XDG_CONFIG_HOME").map(Into::into)
+			.or_else(|| std::env::var_os("APPDATA").map(Into::into))
+			.or_else(|| std::env::var_os("HOME").map(|home| std::path::PathBuf::from(home).join(".config")))
+			.unwrap_or_else(|| ".".into());
+		
+		FileStorage { dir: base.join("tappy-plane") }
+	}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl StorageBackend for FileStorage {
+	fn read (&self, name : &str) -> Option<Vec<u8>> {
+		std::fs::read(self.dir.join(name)).ok()
+	}
+	
+	fn write (&self, name : &str, data : &[u8]) {
+		let path = self.dir.join(name);
+		let result = std::fs::create_dir_all(&self.dir)
+			.and_then(|_| std::fs::write(&path, data));
+		
+		if let Err(e) = result {
+			warn!("Failed to write {}: {}", path.display(), e);
+		}
+	}
+}
+
+/// Stores files in the browser's local storage, hex encoded as it only holds
+/// strings
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+	fn storage () -> Option<web_sys::Storage> {
+		web_sys::window()?.local_storage().ok()?
+	}
+}
+
+#[cfg(target_arch = "wasm32")]
+impl StorageBackend for LocalStorage {
+	fn read (&self, name : &str) -> Option<Vec<u8>> {
+		let hex = Self::storage()?.get_item(&format!("tappy-plane.{}", name)).ok()??;
+		
+		(0..hex.len()).step_by(2)
+			.map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+			.collect()
+	}
+	
+	fn write (&self, name : &str, data : &[u8]) {
+		let hex : String = data.iter().map(|b| format!("{:02x}", b)).collect();
+		let saved = Self::storage()
+			.and_then(|s| s.set_item(&format!("tappy-plane.{}", name), &hex).ok());
+		
+		if saved.is_none() {
+			warn!("Failed to write {} to local storage", name);
+		}
+	}
+}
+
+// Resources
+// =========================================================================
+
+/// Saves and loads settings through whichever backend suits the platform
+#[derive(Resource)]
+pub struct Storage (Box<dyn StorageBackend>);
+
+impl Default for Storage {
+	fn default () -> Self {
+		#[cfg(not(target_arch = "wasm32"))]
+		return Storage(Box::new(FileStorage::default()));
+		
+		#[cfg(target_arch = "wasm32")]
+		return Storage(Box::new(LocalStorage));
+	}
+}
+
+impl Storage {
+	/// Loads `key.ron`
+	pub fn load<T : DeserializeOwned> (&self, key : &str) -> Option<T> {
+		let bytes = self.0.read(&format!("{}.ron", key))?;
+		
+		match ron::de::from_bytes(&bytes) {
+			Ok(value) => Some(value),
+			Err(e) => {
+				warn!("Ignoring saved {}: {}", key, e);
+				None
+			}
+		}
+	}
+	
+	/// Saves `key.ron`
+	pub fn save<T : Serialize> (&self, key : &str, value : &T) {
+		match ron::ser::to_string_pretty(value, default()) {
+			Ok(text) => self.0.write(&format!("{}.ron", key), text.as_bytes()),
+			Err(e) => warn!("Failed to save {}: {}", key, e),
+		}
+	}
+	
+	pub fn read (&self, name : &str) -> Option<Vec<u8>> {
+		self.0.read(name)
+	}
+	
+	pub fn write (&self, name : &str, data : &[u8]) {
+		self.0.write(name, data);
+	}
+}